@@ -1,5 +1,8 @@
 use cumulus_primitives_core::ParaId;
-use parachain_template_runtime::{AccountId, AuraId, Signature, EXISTENTIAL_DEPOSIT};
+use ep_sequencer::{EraConfig, Forcing};
+use parachain_template_runtime::{
+	AccountId, AuraId, Balance, Signature, EXISTENTIAL_DEPOSIT, UNIT,
+};
 use sc_chain_spec::{ChainSpecExtension, ChainSpecGroup};
 use sc_service::ChainType;
 use serde::{Deserialize, Serialize};
@@ -13,6 +16,9 @@ pub type ChainSpec =
 /// The default XCM version to set in genesis config.
 const SAFE_XCM_VERSION: u32 = xcm::prelude::XCM_VERSION;
 
+/// The bond of the genesis sequencers.
+const SEQUENCER_BOND: Balance = 10_000 * UNIT;
+
 /// Helper function to generate a crypto pair from seed
 pub fn get_from_seed<TPublic: Public>(seed: &str) -> <TPublic::Pair as Pair>::Public {
 	TPublic::Pair::from_string(&format!("//{}", seed), None)
@@ -103,6 +109,8 @@ pub fn development_config() -> ChainSpec {
 				],
 				get_account_id_from_seed::<sr25519::Public>("Alice"),
 				1000.into(),
+				// Only two collators, elect them both.
+				Some(EraConfig { sessions_per_era: 6, history_depth: 84, min_sequencer_count: 2 }),
 			)
 		},
 		Vec::new(),
@@ -171,6 +179,7 @@ pub fn local_testnet_config() -> ChainSpec {
 				],
 				get_account_id_from_seed::<sr25519::Public>("Alice"),
 				1000.into(),
+				None,
 			)
 		},
 		// Bootnodes
@@ -196,6 +205,7 @@ fn testnet_genesis(
 	endowed_accounts: Vec<AccountId>,
 	root: AccountId,
 	id: ParaId,
+	era_config: Option<EraConfig>,
 ) -> parachain_template_runtime::RuntimeGenesisConfig {
 	parachain_template_runtime::RuntimeGenesisConfig {
 		system: parachain_template_runtime::SystemConfig {
@@ -216,6 +226,15 @@ fn testnet_genesis(
 			candidacy_bond: EXISTENTIAL_DEPOSIT * 16,
 			..Default::default()
 		},
+		// The collators are the genesis sequencers, in one group each.
+		sequencer: parachain_template_runtime::SequencerConfig {
+			era_config,
+			stakers: invulnerables.iter().map(|(acc, _)| (acc.clone(), SEQUENCER_BOND)).collect(),
+			sequencers_per_group: 1,
+			group_count: invulnerables.len() as u32,
+			force_era: Forcing::NotForcing,
+			sequencers: None,
+		},
 		session: parachain_template_runtime::SessionConfig {
 			keys: invulnerables
 				.into_iter()
@@ -0,0 +1,248 @@
+//! RPC methods of the sequencer pallet, backed by the [`SequencerApi`] runtime API.
+
+use std::sync::Arc;
+
+use ep_sequencer::{
+	ActiveEraInfo, ElectedSequencer, EraIndex, EraTiming, GroupInfo,
+	SequencerApi as SequencerRuntimeApi, SessionIndex,
+};
+use futures::{future, FutureExt, StreamExt};
+use jsonrpsee::{
+	core::{async_trait, RpcResult},
+	proc_macros::rpc,
+	types::{
+		error::{CallError, ErrorObject},
+		SubscriptionResult,
+	},
+	SubscriptionSink,
+};
+use parity_scale_codec::Codec;
+use sc_client_api::BlockchainEvents;
+use sc_rpc::SubscriptionTaskExecutor;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::traits::Block as BlockT;
+
+/// Error code of the runtime API calls failing.
+const RUNTIME_ERROR: i32 = 1;
+
+#[rpc(server)]
+pub trait SequencerApi<BlockHash, AccountId, Balance> {
+	/// The latest planned era, it might not be active yet.
+	#[method(name = "sequencer_currentEra")]
+	fn current_era(&self, at: Option<BlockHash>) -> RpcResult<Option<EraIndex>>;
+
+	/// The active era.
+	#[method(name = "sequencer_activeEra")]
+	fn active_era(&self, at: Option<BlockHash>) -> RpcResult<Option<ActiveEraInfo>>;
+
+	/// The sequencers elected for `era`, the active era by default.
+	#[method(name = "sequencer_sequencers")]
+	fn sequencers(
+		&self,
+		era: Option<EraIndex>,
+		at: Option<BlockHash>,
+	) -> RpcResult<Vec<ElectedSequencer<AccountId, Balance>>>;
+
+	/// The sequencer groups of `era`, the active era by default.
+	#[method(name = "sequencer_groups")]
+	fn groups(
+		&self,
+		era: Option<EraIndex>,
+		at: Option<BlockHash>,
+	) -> RpcResult<Vec<GroupInfo<AccountId, Balance>>>;
+
+	/// The total bonded by the stakers, and their number.
+	#[method(name = "sequencer_restakeTotals")]
+	fn restake_totals(&self, at: Option<BlockHash>) -> RpcResult<(Balance, u32)>;
+
+	/// The group `account` is a member of in `era`, the active era by default, along with its
+	/// index.
+	#[method(name = "sequencer_myGroup")]
+	fn my_group(
+		&self,
+		account: AccountId,
+		era: Option<EraIndex>,
+		at: Option<BlockHash>,
+	) -> RpcResult<Option<(u32, GroupInfo<AccountId, Balance>)>>;
+
+	/// The number of sessions until the next era becomes active.
+	#[method(name = "sequencer_nextEraEtaSessions")]
+	fn next_era_eta_sessions(&self, at: Option<BlockHash>) -> RpcResult<Option<SessionIndex>>;
+
+	/// When `era`, the active era by default, started and how long it lasted, in milliseconds.
+	#[method(name = "sequencer_eraTiming")]
+	fn era_timing(
+		&self,
+		era: Option<EraIndex>,
+		at: Option<BlockHash>,
+	) -> RpcResult<Option<EraTiming>>;
+
+	/// Subscribe to the era changes, the new active era is sent whenever an era starts on the
+	/// best chain.
+	#[subscription(
+		name = "sequencer_subscribeEraChanges" => "sequencer_eraChanged",
+		unsubscribe = "sequencer_unsubscribeEraChanges",
+		item = ActiveEraInfo,
+	)]
+	fn subscribe_era_changes(&self);
+}
+
+/// Implementation of the sequencer RPC methods.
+pub struct Sequencer<C, Block> {
+	client: Arc<C>,
+	executor: SubscriptionTaskExecutor,
+	_marker: std::marker::PhantomData<Block>,
+}
+
+impl<C, Block> Sequencer<C, Block> {
+	/// Create a new instance, spawning the subscriptions on `executor`.
+	pub fn new(client: Arc<C>, executor: SubscriptionTaskExecutor) -> Self {
+		Self { client, executor, _marker: Default::default() }
+	}
+}
+
+/// Turn a runtime API error into an RPC error.
+fn runtime_error(error: impl ToString) -> jsonrpsee::core::Error {
+	CallError::Custom(ErrorObject::owned(
+		RUNTIME_ERROR,
+		"Unable to query the sequencer runtime API",
+		Some(error.to_string()),
+	))
+	.into()
+}
+
+impl<C, Block, AccountId, Balance> Sequencer<C, Block>
+where
+	Block: BlockT,
+	C: ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+	C::Api: SequencerRuntimeApi<Block, AccountId, Balance>,
+	AccountId: Codec,
+	Balance: Codec,
+{
+	/// The block to query at, the best block by default.
+	fn at(&self, at: Option<Block::Hash>) -> Block::Hash {
+		at.unwrap_or_else(|| self.client.info().best_hash)
+	}
+
+	/// The era to query, the active era at `at` by default.
+	fn era_or_active(&self, era: Option<EraIndex>, at: Block::Hash) -> RpcResult<Option<EraIndex>> {
+		match era {
+			Some(era) => Ok(Some(era)),
+			None => Ok(self
+				.client
+				.runtime_api()
+				.active_era(at)
+				.map_err(runtime_error)?
+				.map(|info| info.index)),
+		}
+	}
+}
+
+#[async_trait]
+impl<C, Block, AccountId, Balance> SequencerApiServer<Block::Hash, AccountId, Balance>
+	for Sequencer<C, Block>
+where
+	Block: BlockT,
+	C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + BlockchainEvents<Block>,
+	C: Send + Sync + 'static,
+	C::Api: SequencerRuntimeApi<Block, AccountId, Balance>,
+	AccountId:
+		Codec + Clone + serde::Serialize + serde::de::DeserializeOwned + Send + Sync + 'static,
+	Balance: Codec + serde::Serialize + Send + Sync + 'static,
+{
+	fn current_era(&self, at: Option<Block::Hash>) -> RpcResult<Option<EraIndex>> {
+		self.client.runtime_api().current_era(self.at(at)).map_err(runtime_error)
+	}
+
+	fn active_era(&self, at: Option<Block::Hash>) -> RpcResult<Option<ActiveEraInfo>> {
+		self.client.runtime_api().active_era(self.at(at)).map_err(runtime_error)
+	}
+
+	fn sequencers(
+		&self,
+		era: Option<EraIndex>,
+		at: Option<Block::Hash>,
+	) -> RpcResult<Vec<ElectedSequencer<AccountId, Balance>>> {
+		let at = self.at(at);
+		let Some(era) = self.era_or_active(era, at)? else { return Ok(Vec::new()) };
+		self.client.runtime_api().era_sequencers(at, era).map_err(runtime_error)
+	}
+
+	fn groups(
+		&self,
+		era: Option<EraIndex>,
+		at: Option<Block::Hash>,
+	) -> RpcResult<Vec<GroupInfo<AccountId, Balance>>> {
+		let at = self.at(at);
+		let Some(era) = self.era_or_active(era, at)? else { return Ok(Vec::new()) };
+		self.client.runtime_api().era_groups(at, era).map_err(runtime_error)
+	}
+
+	fn restake_totals(&self, at: Option<Block::Hash>) -> RpcResult<(Balance, u32)> {
+		self.client.runtime_api().restake_totals(self.at(at)).map_err(runtime_error)
+	}
+
+	fn my_group(
+		&self,
+		account: AccountId,
+		era: Option<EraIndex>,
+		at: Option<Block::Hash>,
+	) -> RpcResult<Option<(u32, GroupInfo<AccountId, Balance>)>> {
+		let at = self.at(at);
+		let Some(era) = self.era_or_active(era, at)? else { return Ok(None) };
+		let api = self.client.runtime_api();
+		let Some(index) = api.group_of(at, account, era).map_err(runtime_error)? else {
+			return Ok(None);
+		};
+		let group = api.era_groups(at, era).map_err(runtime_error)?.into_iter().nth(index as usize);
+		Ok(group.map(|group| (index, group)))
+	}
+
+	fn next_era_eta_sessions(&self, at: Option<Block::Hash>) -> RpcResult<Option<SessionIndex>> {
+		self.client
+			.runtime_api()
+			.next_era_eta_sessions(self.at(at))
+			.map_err(runtime_error)
+	}
+
+	fn era_timing(
+		&self,
+		era: Option<EraIndex>,
+		at: Option<Block::Hash>,
+	) -> RpcResult<Option<EraTiming>> {
+		let at = self.at(at);
+		let Some(era) = self.era_or_active(era, at)? else { return Ok(None) };
+		self.client.runtime_api().era_timing(at, era).map_err(runtime_error)
+	}
+
+	fn subscribe_era_changes(&self, mut sink: SubscriptionSink) -> SubscriptionResult {
+		let client = self.client.clone();
+		let mut last_era = client
+			.runtime_api()
+			.active_era(client.info().best_hash)
+			.ok()
+			.flatten()
+			.map(|info| info.index);
+
+		let stream = self.client.import_notification_stream().filter_map(move |notification| {
+			let mut changed = None;
+			if notification.is_new_best {
+				let active_era = client.runtime_api().active_era(notification.hash).ok().flatten();
+				if let Some(info) = active_era {
+					if last_era != Some(info.index) {
+						last_era = Some(info.index);
+						changed = Some(info);
+					}
+				}
+			}
+			future::ready(changed)
+		});
+
+		let fut = async move {
+			sink.pipe_from_stream(stream).await;
+		};
+		self.executor.spawn("sequencer-rpc-subscription", Some("rpc"), fut.boxed());
+		Ok(())
+	}
+}
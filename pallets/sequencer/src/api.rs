@@ -0,0 +1,64 @@
+//! Queries backing the [`ep_sequencer::SequencerApi`] runtime API.
+
+use super::*;
+use ep_sequencer::{EraTiming, GroupInfo};
+
+impl<T: Config> Pallet<T> {
+	/// The sequencer groups of `era`.
+	pub fn api_era_groups(era: EraIndex) -> Vec<GroupInfo<T::AccountId, BalanceOf<T>>> {
+		Self::eras_sequencer_groups(era)
+			.into_iter()
+			.map(|group| GroupInfo {
+				members: group.members.into_inner(),
+				total_stake: group.total_stake,
+			})
+			.collect()
+	}
+
+	/// When `era` started and how long it lasted, `None` if it has not started or is out of
+	/// history.
+	pub fn api_era_timing(era: EraIndex) -> Option<EraTiming> {
+		let start = Self::eras_start_timestamp(era)?;
+		Some(EraTiming { start, duration: Self::eras_duration(era) })
+	}
+
+	/// The total bonded by the stakers, and their number.
+	pub fn api_restake_totals() -> (BalanceOf<T>, u32) {
+		(Self::total_restake(), Self::restaker_count())
+	}
+
+	/// The index of the group `who` is a member of in `era`.
+	pub fn api_group_of(who: &T::AccountId, era: EraIndex) -> Option<u32> {
+		Self::eras_sequencer_groups(era)
+			.iter()
+			.position(|group| group.members.contains(who))
+			.map(|index| index as u32)
+	}
+
+	/// The number of sessions from `current_session` until the next era becomes active.
+	///
+	/// If the next era is not planned yet, it is estimated from the forcing mode and the length
+	/// of the current era. Eras are planned for the session after the queued one at the
+	/// earliest, since the session pallet queues the validators one session ahead. `None` if
+	/// eras are forced off.
+	pub fn api_next_era_eta_sessions(current_session: SessionIndex) -> Option<SessionIndex> {
+		let active_era = Self::active_era().map(|info| info.index);
+		let start = match Self::current_era() {
+			// The next era is already planned.
+			Some(planned) if active_era.map_or(true, |active| planned > active) =>
+				Self::eras_start_session_index(planned)?,
+			current_era => {
+				let earliest = current_session.saturating_add(2);
+				match (Self::force_era(), current_era) {
+					(Forcing::ForceNone, _) => return None,
+					(Forcing::NotForcing, Some(era)) =>
+						Self::eras_start_session_index(era)?
+							.saturating_add(Self::current_config().sessions_per_era)
+							.max(earliest),
+					_ => earliest,
+				}
+			},
+		};
+		Some(start.saturating_sub(current_session))
+	}
+}
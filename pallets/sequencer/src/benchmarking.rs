@@ -4,16 +4,392 @@ use super::*;
 
 #[allow(unused)]
 use crate::Pallet as Sequencer;
-use frame_benchmarking::{benchmarks, impl_benchmark_test_suite, whitelisted_caller};
+use frame_benchmarking::{
+	account, benchmarks, impl_benchmark_test_suite, whitelist_account, whitelisted_caller,
+	BenchmarkError,
+};
+use frame_support::traits::{fungible::Mutate, EnsureOrigin};
 use frame_system::RawOrigin;
+use sp_runtime::traits::One;
+use sp_std::{collections::btree_map::BTreeMap, vec};
+
+const SEED: u32 = 0;
+
+/// Upper bound of the number of delegations in the election benchmark.
+const MAX_ELECTION_DELEGATIONS: u32 = 1_000;
+
+/// The stake the stakers of the benchmarks bond or delegate.
+fn stake<T: Config>() -> BalanceOf<T> {
+	T::Currency::minimum_balance().max(One::one()).saturating_mul(1_000u32.into())
+}
+
+/// The info of an operator, every field filled up to the limits.
+fn max_info<T: Config>() -> SequencerInfo<T> {
+	let field = InfoField::<T>::truncate_from(vec![b'x'; T::MaxFieldLength::get() as usize]);
+	let endpoints =
+		Endpoints::<T>::truncate_from(vec![field.clone(); T::MaxEndpoints::get() as usize]);
+	SequencerInfo {
+		display_name: field.clone(),
+		rpc_endpoints: endpoints.clone(),
+		p2p_endpoints: endpoints,
+		contact: field,
+	}
+}
+
+/// The info of an operator, every field empty.
+fn min_info<T: Config>() -> SequencerInfo<T> {
+	SequencerInfo {
+		display_name: Default::default(),
+		rpc_endpoints: Default::default(),
+		p2p_endpoints: Default::default(),
+		contact: Default::default(),
+	}
+}
+
+/// Give `who` enough funds to bond, delegate and register with [`max_info`] many times over.
+fn fund<T: Config>(who: &T::AccountId)
+where
+	T::Currency: Mutate<T::AccountId>,
+{
+	let balance = stake::<T>()
+		.saturating_mul(1_000u32.into())
+		.saturating_add(Sequencer::<T>::registration_deposit(&max_info::<T>()));
+	T::Currency::set_balance(who, balance);
+}
+
+/// A funded account.
+fn funded_account<T: Config>(name: &'static str, index: u32) -> T::AccountId
+where
+	T::Currency: Mutate<T::AccountId>,
+{
+	let who = account(name, index, SEED);
+	fund::<T>(&who);
+	who
+}
+
+/// A registered operator bonding `stake` times `factor`.
+fn create_operator<T: Config>(index: u32, factor: u32) -> T::AccountId
+where
+	T::Currency: Mutate<T::AccountId>,
+{
+	let operator = funded_account::<T>("operator", index);
+	Sequencer::<T>::do_bond(operator.clone(), stake::<T>().saturating_mul(factor.into()))
+		.expect("the operator is funded; qed");
+	Sequencer::<T>::do_register_sequencer(operator.clone(), min_info::<T>(), Perbill::zero())
+		.expect("the operator is funded and not registered; qed");
+	operator
+}
+
+/// Add `count` delegators to `operator`.
+fn create_delegators<T: Config>(operator: &T::AccountId, count: u32) -> Vec<T::AccountId>
+where
+	T::Currency: Mutate<T::AccountId>,
+{
+	(0..count)
+		.map(|index| {
+			let delegator = funded_account::<T>("delegator", index);
+			Sequencer::<T>::do_delegate(delegator.clone(), operator.clone(), stake::<T>())
+				.expect("the delegator is funded and the operator registered; qed");
+			delegator
+		})
+		.collect()
+}
+
+/// Record `operator` as elected in era 0, backed by its delegators, and make era 1 active.
+fn elect_in_era_zero<T: Config>(operator: &T::AccountId) {
+	let exposure = Sequencer::<T>::exposure_of(operator);
+	let sequencer = ElectedSequencer { who: operator.clone(), stake: exposure.total, rank: 0 };
+	EraInfo::<T>::set_sequencer(0, BoundedVec::truncate_from(vec![sequencer]));
+	EraInfo::<T>::set_exposures(0, vec![(operator.clone(), exposure)]);
+	CurrentEra::<T>::put(1);
+	ActiveEra::<T>::put(ActiveEraInfo { index: 1, start: None });
+}
 
 benchmarks! {
-	do_something {
-		let s in 0 .. 100;
-		let caller: T::AccountId = whitelisted_caller();
-	}: _(RawOrigin::Signed(caller), s)
+	where_clause { where T::Currency: Mutate<T::AccountId> }
+
+	bond {
+		// Another staker, so the caller lands in an existing bag.
+		create_operator::<T>(0, 1);
+		let caller = funded_account::<T>("caller", 0);
+		whitelist_account!(caller);
+	}: _(RawOrigin::Signed(caller.clone()), stake::<T>())
+	verify {
+		assert_eq!(RestakeData::<T>::get(&caller), stake::<T>());
+	}
+
+	bond_extra {
+		create_operator::<T>(0, 1);
+		let caller = funded_account::<T>("caller", 0);
+		Sequencer::<T>::do_bond(caller.clone(), stake::<T>())?;
+		whitelist_account!(caller);
+		// Enough to move the caller to another bag.
+		let extra = stake::<T>().saturating_mul(10u32.into());
+	}: _(RawOrigin::Signed(caller.clone()), extra)
+	verify {
+		assert_eq!(RestakeData::<T>::get(&caller), stake::<T>().saturating_mul(11u32.into()));
+	}
+
+	unbond {
+		create_operator::<T>(0, 1);
+		let caller = funded_account::<T>("caller", 0);
+		Sequencer::<T>::do_bond(caller.clone(), stake::<T>().saturating_mul(11u32.into()))?;
+		// All the chunks but one are taken, the new one is pushed at the end.
+		for _ in 1..T::MaxUnlockingChunks::get() {
+			let era = Sequencer::<T>::schedule_unlock(&caller, One::one())?;
+			let next_era = era.saturating_sub(T::BondingDuration::get()).saturating_add(1);
+			CurrentEra::<T>::put(next_era);
+		}
+		whitelist_account!(caller);
+		let amount = stake::<T>().saturating_mul(10u32.into());
+	}: _(RawOrigin::Signed(caller.clone()), amount)
+	verify {
+		assert_eq!(RestakeData::<T>::get(&caller), stake::<T>());
+	}
+
+	withdraw_unbonded {
+		let caller = funded_account::<T>("caller", 0);
+		Sequencer::<T>::do_bond(caller.clone(), stake::<T>())?;
+		// All the chunks are taken and withdrawn at once.
+		let chunk = stake::<T>() / T::MaxUnlockingChunks::get().max(1).into();
+		for era in 0..T::MaxUnlockingChunks::get() {
+			CurrentEra::<T>::put(era);
+			Sequencer::<T>::schedule_unlock(&caller, chunk)?;
+		}
+		let last_era = T::MaxUnlockingChunks::get().saturating_add(T::BondingDuration::get());
+		CurrentEra::<T>::put(last_era);
+		whitelist_account!(caller);
+	}: _(RawOrigin::Signed(caller.clone()))
+	verify {
+		assert!(!Unlocking::<T>::contains_key(&caller));
+	}
+
+	set_sequencers_per_group {
+	}: _(RawOrigin::Root, 4)
+	verify {
+		assert_eq!(SequencersPerGroup::<T>::get(), 4);
+	}
+
+	set_group_count {
+	}: _(RawOrigin::Root, 4)
+	verify {
+		assert_eq!(GroupCount::<T>::get(), 4);
+	}
+
+	force_new_era {
+		let origin =
+			T::AdminOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+	}: _<T::RuntimeOrigin>(origin)
+	verify {
+		assert_eq!(ForceEra::<T>::get(), Forcing::ForceNew);
+	}
+
+	force_no_eras {
+		let origin =
+			T::AdminOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+	}: _<T::RuntimeOrigin>(origin)
 	verify {
-		assert_eq!(Something::<T>::get(), Some(s));
+		assert_eq!(ForceEra::<T>::get(), Forcing::ForceNone);
+	}
+
+	force_new_era_always {
+		let origin =
+			T::AdminOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+	}: _<T::RuntimeOrigin>(origin)
+	verify {
+		assert_eq!(ForceEra::<T>::get(), Forcing::ForceAlways);
+	}
+
+	report_offence {
+		let d in 0 .. T::MaxDelegators::get();
+		let operator = create_operator::<T>(0, 1);
+		create_delegators::<T>(&operator, d);
+		elect_in_era_zero::<T>(&operator);
+		let origin =
+			T::AdminOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+	}: _<T::RuntimeOrigin>(origin, 0, operator.clone(), Perbill::from_percent(10))
+	verify {
+		let apply_era = 1u32.saturating_add(T::SlashDeferDuration::get());
+		assert!(
+			!UnappliedSlashes::<T>::get(apply_era).is_empty() ||
+				!ErasSlashes::<T>::get(0, &operator).is_zero()
+		);
+	}
+
+	cancel_deferred_slash {
+		let s in 1 .. T::MaxUnappliedSlashes::get();
+		let slashes = (0..s)
+			.map(|index| UnappliedSlash {
+				sequencer: account("sequencer", index, SEED),
+				era: 0,
+				own: stake::<T>(),
+				others: BoundedVec::new(),
+			})
+			.collect::<Vec<_>>();
+		UnappliedSlashes::<T>::insert(1, BoundedVec::truncate_from(slashes));
+		let origin =
+			T::AdminOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+	}: _<T::RuntimeOrigin>(origin, 1, (0..s).collect())
+	verify {
+		assert!(!UnappliedSlashes::<T>::contains_key(1));
+	}
+
+	payout_era {
+		let d in 0 .. T::MaxDelegators::get();
+		let operator = create_operator::<T>(0, 1);
+		create_delegators::<T>(&operator, d);
+		Commissions::<T>::insert(&operator, Perbill::from_percent(10));
+		elect_in_era_zero::<T>(&operator);
+		ErasSequencerReward::<T>::insert(0, stake::<T>());
+		ErasRewardPoints::<T>::insert(
+			0,
+			EraRewardPoints { total: 20, individual: BTreeMap::from([(operator.clone(), 20)]) },
+		);
+		let caller = whitelisted_caller();
+	}: _(RawOrigin::Signed(caller), 0, operator.clone())
+	verify {
+		assert!(ClaimedRewards::<T>::get(0, &operator));
+	}
+
+	delegate {
+		let operator = create_operator::<T>(0, 1);
+		create_delegators::<T>(&operator, 1);
+		let caller = funded_account::<T>("caller", 0);
+		whitelist_account!(caller);
+		// Enough to move the operator to another bag.
+		let amount = stake::<T>().saturating_mul(10u32.into());
+	}: _(RawOrigin::Signed(caller.clone()), operator.clone(), amount)
+	verify {
+		assert_eq!(Delegations::<T>::get(&operator, &caller), amount);
+	}
+
+	undelegate {
+		let operator = create_operator::<T>(0, 1);
+		let caller = funded_account::<T>("caller", 0);
+		let amount = stake::<T>().saturating_mul(10u32.into());
+		Sequencer::<T>::do_delegate(caller.clone(), operator.clone(), amount)?;
+		whitelist_account!(caller);
+	}: _(RawOrigin::Signed(caller.clone()), operator.clone(), amount)
+	verify {
+		assert!(!Delegations::<T>::contains_key(&operator, &caller));
+	}
+
+	register_sequencer {
+		let caller = funded_account::<T>("caller", 0);
+		whitelist_account!(caller);
+	}: _(RawOrigin::Signed(caller.clone()), max_info::<T>(), Perbill::from_percent(10))
+	verify {
+		assert_eq!(Commissions::<T>::get(&caller), Perbill::from_percent(10));
+	}
+
+	update_sequencer {
+		let caller = funded_account::<T>("caller", 0);
+		Sequencer::<T>::do_register_sequencer(caller.clone(), min_info::<T>(), Perbill::zero())?;
+		whitelist_account!(caller);
+	}: _(RawOrigin::Signed(caller.clone()), max_info::<T>())
+	verify {
+		let info = Registrations::<T>::get(&caller).map(|registration| registration.info);
+		assert_eq!(info, Some(max_info::<T>()));
+	}
+
+	deregister_sequencer {
+		let caller = funded_account::<T>("caller", 0);
+		Sequencer::<T>::do_register_sequencer(caller.clone(), max_info::<T>(), Perbill::zero())?;
+		whitelist_account!(caller);
+	}: _(RawOrigin::Signed(caller.clone()))
+	verify {
+		assert!(!Registrations::<T>::contains_key(&caller));
+	}
+
+	set_config {
+		let config = EraConfig {
+			sessions_per_era: 6,
+			history_depth: 84,
+			min_sequencer_count: T::MinSequencerCount::get(),
+		};
+		let origin =
+			T::AdminOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+	}: _<T::RuntimeOrigin>(origin, config)
+	verify {
+		assert_eq!(PendingConfig::<T>::get(), Some(config));
+	}
+
+	// The election run at the end of the session planning a new era, out of `v` validators
+	// backed by `r` delegations.
+	elect_sequencers {
+		let v in T::MinSequencerCount::get().max(1) .. T::MaxCandidates::get();
+		let r in 0 .. MAX_ELECTION_DELEGATIONS;
+		let validators =
+			(0..v).map(|index| create_operator::<T>(index, index + 1)).collect::<Vec<_>>();
+		// The election only reads the delegations, they do not need to be held.
+		for index in 0..r {
+			let delegator: T::AccountId = account("delegator", index, SEED);
+			let operator = &validators[(index % v) as usize];
+			Delegations::<T>::insert(operator, &delegator, stake::<T>());
+		}
+		// As many seats as the election can fill.
+		GroupCount::<T>::put(T::MaxSequencers::get());
+		SequencersPerGroup::<T>::put(1);
+	}: {
+		Sequencer::<T>::elect_sequencers(&validators).map_err(|_| "the election failed")?;
+	}
+
+	// The grouping of `MaxSequencers` elected sequencers into `g` groups.
+	form_groups {
+		let g in 1 .. T::MaxSequencers::get();
+		let sequencers = BoundedVec::truncate_from(
+			(0..T::MaxSequencers::get())
+				.map(|index| ElectedSequencer {
+					who: account("sequencer", index, SEED),
+					stake: stake::<T>().saturating_mul((index + 1).into()),
+					rank: index,
+				})
+				.collect::<Vec<_>>(),
+		);
+		GroupCount::<T>::put(g);
+		SequencersPerGroup::<T>::put(T::MaxSequencers::get() / g);
+		let seed = T::Hash::default();
+	}: {
+		let (groups, standby) = Sequencer::<T>::form_groups(&sequencers, &seed);
+		EraInfo::<T>::set_groups(0, groups, standby, seed)?;
+	}
+	verify {
+		assert_eq!(ErasSequencerGroups::<T>::get(0).len() as u32, g);
+	}
+
+	// Storing the exposures of `s` elected sequencers, backed by `MaxDelegators` delegators
+	// each, along with their commission.
+	set_exposures {
+		let s in 1 .. T::MaxSequencers::get();
+		let exposures = (0..s)
+			.map(|index| {
+				let others = (0..T::MaxDelegators::get())
+					.map(|delegator| IndividualExposure {
+						who: account("delegator", delegator, SEED),
+						value: stake::<T>(),
+					})
+					.collect::<Vec<_>>();
+				let total = stake::<T>().saturating_mul((others.len() as u32 + 1).into());
+				let exposure = Exposure { total, own: stake::<T>(), others };
+				(account("sequencer", index, SEED), exposure)
+			})
+			.collect::<Vec<_>>();
+	}: {
+		EraInfo::<T>::set_exposures(0, exposures);
+	}
+	verify {
+		assert_eq!(ErasExposures::<T>::iter_prefix(0).count() as u32, s);
+	}
+
+	// The scan of `s` stakers of the list for the candidates. Without session keys, none of the
+	// operators is a candidate and the whole list is read.
+	top_candidates {
+		let s in 1 .. Sequencer::<T>::max_candidate_scan();
+		for index in 0..s {
+			create_operator::<T>(index, index + 1);
+		}
+	}: {
+		Sequencer::<T>::top_candidates();
 	}
 }
 
@@ -0,0 +1,107 @@
+//! Delegated restaking.
+//!
+//! Accounts which do not run a sequencer can back an operator by delegating funds to it. The
+//! delegated funds are held on [`Config::Currency`] like the operator's own bond, and count
+//! toward the operator's election weight, see [`Pallet::election_weight`].
+//!
+//! The stake backing an elected operator is recorded in [`ErasExposures`], along with its
+//! commission in [`ErasCommissions`]. The era payouts and slashes are split between the operator
+//! and its delegators out of this snapshot.
+//!
+//! Undelegated funds go through the same unbonding queue as the operator's own funds.
+
+use super::*;
+
+impl<T: Config> Pallet<T> {
+	/// Delegate `amount` of `delegator`'s funds to `operator`.
+	pub fn do_delegate(
+		delegator: T::AccountId,
+		operator: T::AccountId,
+		amount: BalanceOf<T>,
+	) -> DispatchResult {
+		ensure!(delegator != operator, Error::<T>::SelfDelegation);
+		ensure!(Registrations::<T>::contains_key(&operator), Error::<T>::NotOperator);
+		ensure!(!amount.is_zero(), Error::<T>::InsufficientBond);
+
+		if !Delegations::<T>::contains_key(&operator, &delegator) {
+			DelegatorCount::<T>::try_mutate(&operator, |count| {
+				ensure!(*count < T::MaxDelegators::get(), Error::<T>::TooManyDelegators);
+				*count += 1;
+				Ok::<_, Error<T>>(())
+			})?;
+		}
+
+		T::Currency::hold(&HoldReason::Restaking.into(), &delegator, amount)?;
+		Delegations::<T>::mutate(&operator, &delegator, |delegated| {
+			*delegated = delegated.saturating_add(amount)
+		});
+		TotalDelegated::<T>::mutate(&operator, |total| *total = total.saturating_add(amount));
+		Self::update_staker_list(&operator);
+
+		Self::deposit_event(Event::<T>::Delegated { delegator, operator, amount });
+		Ok(())
+	}
+
+	/// Undelegate `amount` of the funds `delegator` delegated to `operator`.
+	///
+	/// The funds can be withdrawn once [`Config::BondingDuration`] eras have passed.
+	pub fn do_undelegate(
+		delegator: T::AccountId,
+		operator: T::AccountId,
+		amount: BalanceOf<T>,
+	) -> DispatchResult {
+		let delegated = Delegations::<T>::get(&operator, &delegator);
+		ensure!(!delegated.is_zero(), Error::<T>::NotDelegated);
+
+		let amount = amount.min(delegated);
+		ensure!(!amount.is_zero(), Error::<T>::InsufficientBond);
+
+		let era = Self::schedule_unlock(&delegator, amount)?;
+		Self::reduce_delegation(&operator, &delegator, amount);
+
+		Self::deposit_event(Event::<T>::Undelegated { delegator, operator, amount, era });
+		Ok(())
+	}
+
+	/// Take `amount` out of the delegation of `delegator` to `operator`, removing the delegation
+	/// once it is empty.
+	pub(crate) fn reduce_delegation(
+		operator: &T::AccountId,
+		delegator: &T::AccountId,
+		amount: BalanceOf<T>,
+	) {
+		let delegated = Delegations::<T>::get(operator, delegator);
+		let amount = amount.min(delegated);
+		let remaining = delegated.saturating_sub(amount);
+		if remaining.is_zero() {
+			Delegations::<T>::remove(operator, delegator);
+			DelegatorCount::<T>::mutate(operator, |count| *count = count.saturating_sub(1));
+		} else {
+			Delegations::<T>::insert(operator, delegator, remaining);
+		}
+
+		TotalDelegated::<T>::mutate_exists(operator, |maybe_total| {
+			let total = maybe_total.unwrap_or_default().saturating_sub(amount);
+			*maybe_total = if total.is_zero() { None } else { Some(total) };
+		});
+		Self::update_staker_list(operator);
+	}
+
+	/// The weight of `operator` in the elections, its own stake plus the delegations.
+	pub fn election_weight(operator: &T::AccountId) -> BalanceOf<T> {
+		Self::restake_data(operator).saturating_add(Self::total_delegated(operator))
+	}
+
+	/// The stake currently backing `operator`.
+	pub fn exposure_of(operator: &T::AccountId) -> Exposure<T::AccountId, BalanceOf<T>> {
+		let own = Self::restake_data(operator);
+		let others = Delegations::<T>::iter_prefix(operator)
+			.map(|(who, value)| IndividualExposure { who, value })
+			.collect::<Vec<_>>();
+		let total = others
+			.iter()
+			.fold(own, |total, delegation| total.saturating_add(delegation.value));
+
+		Exposure { total, own, others }
+	}
+}
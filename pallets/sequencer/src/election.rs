@@ -0,0 +1,222 @@
+//! Strategies electing the sequencers of an era out of the candidates.
+
+use ep_sequencer::{Exposure, IndividualExposure};
+use frame_support::{traits::Randomness, BoundedVec};
+use frame_system::pallet_prelude::BlockNumberFor;
+use parity_scale_codec::{Decode, Encode, MaxEncodedLen};
+use scale_info::TypeInfo;
+use sp_core::{hashing::blake2_256, Get};
+use sp_runtime::{
+	traits::{AtLeast32BitUnsigned, One, Saturating, Zero},
+	RuntimeDebug, SaturatedConversion,
+};
+use sp_std::{cmp::Ordering, collections::btree_map::BTreeMap, vec::Vec};
+
+/// Subject used to draw the seed of [`StakeWeightedSampling`].
+const SAMPLING_SUBJECT: &[u8] = b"sequencer_election";
+
+/// Reasons an election can fail for.
+#[derive(Copy, Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub enum ElectionError {
+	/// There is no candidate at all.
+	NoCandidates,
+	/// More sequencers were elected than the bound allows.
+	TooManyWinners,
+	/// Less sequencers were elected than the minimum.
+	NotEnoughWinners,
+	/// The election algorithm failed.
+	Algorithm,
+}
+
+/// The stake the elections run on.
+#[derive(Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct StakeGraph<AccountId, Balance> {
+	/// The candidate operators, with their own stake.
+	pub candidates: Vec<(AccountId, Balance)>,
+	/// The delegations to the candidates, as `(delegator, operator, amount)`.
+	pub delegations: Vec<(AccountId, AccountId, Balance)>,
+}
+
+/// Order by stake descending, ties broken by the ascending order of the account id.
+pub fn by_rank<AccountId: Ord, Balance: Ord>(
+	a: &(AccountId, Balance),
+	b: &(AccountId, Balance),
+) -> Ordering {
+	b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0))
+}
+
+/// Something electing the sequencers out of the candidates.
+pub trait SequencerElection<AccountId, Balance> {
+	/// Elect the sequencers of an era out of `candidates`.
+	///
+	/// `candidates` are the registered operators with their election weight, ordered by weight
+	/// descending, ties broken by the ascending order of the account id. `seats` is the number of
+	/// sequencers the groups need, `min_count` the number of sequencers to elect at least, if
+	/// there are enough candidates.
+	fn elect<Bound: Get<u32>>(
+		candidates: Vec<(AccountId, Balance)>,
+		seats: u32,
+		min_count: u32,
+	) -> Result<BoundedVec<(AccountId, Balance), Bound>, ElectionError>;
+
+	/// Elect the sequencers out of `graph`, along with the stake backing each of them.
+	///
+	/// By default, the candidates are weighted by their own stake plus the delegations to them
+	/// and elected with [`SequencerElection::elect`]. Every elected sequencer is then backed by
+	/// exactly what is delegated to it.
+	fn elect_exposures<Bound: Get<u32>>(
+		graph: StakeGraph<AccountId, Balance>,
+		seats: u32,
+		min_count: u32,
+	) -> Result<BoundedVec<(AccountId, Exposure<AccountId, Balance>), Bound>, ElectionError>
+	where
+		AccountId: Ord + Clone,
+		Balance: AtLeast32BitUnsigned + Copy,
+	{
+		let mut exposures = graph
+			.candidates
+			.into_iter()
+			.map(|(who, own)| (who, Exposure { total: own, own, others: Vec::new() }))
+			.collect::<BTreeMap<_, _>>();
+		for (delegator, operator, value) in graph.delegations {
+			if let Some(exposure) = exposures.get_mut(&operator) {
+				exposure.total = exposure.total.saturating_add(value);
+				exposure.others.push(IndividualExposure { who: delegator, value });
+			}
+		}
+
+		let mut candidates = exposures
+			.iter()
+			.map(|(who, exposure)| (who.clone(), exposure.total))
+			.collect::<Vec<_>>();
+		candidates.sort_by(by_rank);
+
+		Self::elect::<Bound>(candidates, seats, min_count)?
+			.into_iter()
+			.filter_map(|(who, _)| exposures.remove(&who).map(|exposure| (who, exposure)))
+			.collect::<Vec<_>>()
+			.try_into()
+			.map_err(|_| ElectionError::TooManyWinners)
+	}
+}
+
+/// Elect the candidates staking at least two thirds of the average stake of the candidates.
+///
+/// If less than `min_count` candidates pass the threshold, the best ranked of the others are
+/// elected until there are `min_count`. If more pass, only the best ranked `seats` of them are
+/// elected, and never more than the bound.
+pub struct Threshold;
+
+impl<AccountId: Ord, Balance: AtLeast32BitUnsigned + Copy> SequencerElection<AccountId, Balance>
+	for Threshold
+{
+	fn elect<Bound: Get<u32>>(
+		mut candidates: Vec<(AccountId, Balance)>,
+		seats: u32,
+		min_count: u32,
+	) -> Result<BoundedVec<(AccountId, Balance), Bound>, ElectionError> {
+		if candidates.is_empty() {
+			return Err(ElectionError::NoCandidates);
+		}
+
+		let total_stake = candidates
+			.iter()
+			.fold(Balance::zero(), |total, (_, stake)| total.saturating_add(*stake));
+		let average_stake = total_stake / Balance::from(candidates.len() as u32);
+		let threshold = (average_stake.saturating_mul(2u32.into()) / 3u32.into()).max(One::one());
+
+		// Once ranked, the candidates passing the threshold come first.
+		candidates.sort_by(by_rank);
+		let passing = candidates.iter().take_while(|(_, stake)| *stake >= threshold).count();
+		let seats = seats.max(min_count).min(Bound::get());
+		candidates.truncate(passing.max(min_count as usize).min(seats as usize));
+
+		candidates.try_into().map_err(|_| ElectionError::TooManyWinners)
+	}
+}
+
+/// Elect the `seats` best ranked candidates, at least `min_count` of them.
+pub struct TopN;
+
+impl<AccountId, Balance> SequencerElection<AccountId, Balance> for TopN {
+	fn elect<Bound: Get<u32>>(
+		candidates: Vec<(AccountId, Balance)>,
+		seats: u32,
+		min_count: u32,
+	) -> Result<BoundedVec<(AccountId, Balance), Bound>, ElectionError> {
+		if candidates.is_empty() {
+			return Err(ElectionError::NoCandidates);
+		}
+
+		candidates
+			.into_iter()
+			.take(seats.max(min_count) as usize)
+			.collect::<Vec<_>>()
+			.try_into()
+			.map_err(|_| ElectionError::TooManyWinners)
+	}
+}
+
+/// Sample `seats` candidates, at least `min_count`, with a probability proportional to their
+/// stake.
+///
+/// Candidates are drawn one after the other without replacement. The draw `i` picks the
+/// candidate the cumulated stake of which first exceeds `r(i) % remaining_stake`, where `r(i)` is
+/// the little endian `u128` made of the first sixteen bytes of
+/// `blake2_256(seed.encode() ++ (i as u32).encode())` and `seed` comes from `R`. Candidates
+/// without stake are only elected once all the staked ones are, in rank order.
+pub struct StakeWeightedSampling<T, R>(sp_std::marker::PhantomData<(T, R)>);
+
+impl<AccountId, Balance, T, R> SequencerElection<AccountId, Balance> for StakeWeightedSampling<T, R>
+where
+	Balance: AtLeast32BitUnsigned + Copy,
+	T: frame_system::Config,
+	R: Randomness<T::Hash, BlockNumberFor<T>>,
+{
+	fn elect<Bound: Get<u32>>(
+		mut candidates: Vec<(AccountId, Balance)>,
+		seats: u32,
+		min_count: u32,
+	) -> Result<BoundedVec<(AccountId, Balance), Bound>, ElectionError> {
+		if candidates.is_empty() {
+			return Err(ElectionError::NoCandidates);
+		}
+
+		let (seed, _) = R::random(SAMPLING_SUBJECT);
+		let seats = (seats.max(min_count) as usize).min(candidates.len());
+		let mut remaining_stake = candidates.iter().fold(0u128, |total, (_, stake)| {
+			total.saturating_add((*stake).saturated_into::<u128>())
+		});
+
+		let mut elected = Vec::with_capacity(seats);
+		for draw in 0..seats {
+			let index = if remaining_stake == 0 {
+				// Only unstaked candidates are left, take them in rank order.
+				0
+			} else {
+				let hash = blake2_256(&(&seed, draw as u32).encode());
+				let mut bytes = [0u8; 16];
+				bytes.copy_from_slice(&hash[0..16]);
+				let mut target = u128::from_le_bytes(bytes) % remaining_stake;
+				candidates
+					.iter()
+					.position(|(_, stake)| {
+						let stake = (*stake).saturated_into::<u128>();
+						if target < stake {
+							true
+						} else {
+							target -= stake;
+							false
+						}
+					})
+					.unwrap_or(0)
+			};
+
+			let candidate = candidates.remove(index);
+			remaining_stake = remaining_stake.saturating_sub(candidate.1.saturated_into::<u128>());
+			elected.push(candidate);
+		}
+
+		elected.try_into().map_err(|_| ElectionError::TooManyWinners)
+	}
+}
@@ -0,0 +1,121 @@
+//! Strategies assigning the elected sequencers to their groups.
+
+use parity_scale_codec::Encode;
+use sp_core::hashing::blake2_256;
+use sp_runtime::Saturating;
+use sp_std::vec::Vec;
+
+/// Something that can distribute the elected sequencers over their groups.
+pub trait GroupAssignment<AccountId, Balance> {
+	/// Distribute `sequencers` over `group_count` groups of `group_size` members.
+	///
+	/// `sequencers` holds exactly `group_count * group_size` entries, in the order they should be
+	/// considered. Every returned group must hold exactly `group_size` members.
+	fn assign(
+		sequencers: Vec<(AccountId, Balance)>,
+		group_count: usize,
+		group_size: usize,
+	) -> Vec<Vec<(AccountId, Balance)>>;
+}
+
+/// Fill the groups one after the other, in the order given.
+///
+/// Without shuffling, the highest staked sequencers all end up in the first group.
+pub struct RankOrder;
+
+impl<AccountId, Balance> GroupAssignment<AccountId, Balance> for RankOrder {
+	fn assign(
+		sequencers: Vec<(AccountId, Balance)>,
+		group_count: usize,
+		group_size: usize,
+	) -> Vec<Vec<(AccountId, Balance)>> {
+		let mut groups = Vec::with_capacity(group_count);
+		let mut sequencers = sequencers.into_iter();
+		for _ in 0..group_count {
+			groups.push(sequencers.by_ref().take(group_size).collect());
+		}
+		groups
+	}
+}
+
+/// Snake draft on stake.
+///
+/// Sequencers are ordered by stake descending and picked in rounds of `group_count`, the pick
+/// order being reversed on every round: `0, 1, .., k - 1, k - 1, .., 1, 0, 0, 1, ..`. Within a
+/// round, the sequencers are picked in the order given, so a shuffled order still decides which
+/// group every sequencer of the round joins, while every group gets one sequencer per round.
+pub struct SnakeDraft;
+
+impl<AccountId, Balance: Ord> GroupAssignment<AccountId, Balance> for SnakeDraft {
+	fn assign(
+		sequencers: Vec<(AccountId, Balance)>,
+		group_count: usize,
+		group_size: usize,
+	) -> Vec<Vec<(AccountId, Balance)>> {
+		let mut groups: Vec<Vec<_>> =
+			(0..group_count).map(|_| Vec::with_capacity(group_size)).collect();
+		if group_count == 0 {
+			return groups;
+		}
+
+		let mut ranked = sequencers.into_iter().enumerate().collect::<Vec<_>>();
+		// stable, the given order breaks the ties.
+		ranked.sort_by(|(_, a), (_, b)| b.1.cmp(&a.1));
+		// Only the stake decides the round, the given order decides the picks within it.
+		for round in ranked.chunks_mut(group_count) {
+			round.sort_by_key(|(given, _)| *given);
+		}
+		for (pick, (_, sequencer)) in ranked.into_iter().enumerate() {
+			let (round, position) = (pick / group_count, pick % group_count);
+			let group = if round % 2 == 0 { position } else { group_count - 1 - position };
+			groups[group].push(sequencer);
+		}
+		groups
+	}
+}
+
+/// Greedy bin-packing on stake.
+///
+/// Every sequencer, in the order given, joins the group which is not complete yet and has the
+/// lowest total stake, the lowest group index winning ties. Given in rank order, this is the
+/// classic longest-processing-time heuristic. Given in shuffled order, the groups are random
+/// while the stake spread between them stays bounded by the largest single stake.
+pub struct GreedyBalance;
+
+impl<AccountId, Balance: Ord + Copy + Saturating + Default> GroupAssignment<AccountId, Balance>
+	for GreedyBalance
+{
+	fn assign(
+		sequencers: Vec<(AccountId, Balance)>,
+		group_count: usize,
+		group_size: usize,
+	) -> Vec<Vec<(AccountId, Balance)>> {
+		let mut groups: Vec<Vec<_>> =
+			(0..group_count).map(|_| Vec::with_capacity(group_size)).collect();
+		let mut totals = sp_std::vec![Balance::default(); group_count];
+
+		for (who, stake) in sequencers {
+			let lightest = (0..group_count)
+				.filter(|group| groups[*group].len() < group_size)
+				.min_by_key(|group| totals[*group]);
+			if let Some(group) = lightest {
+				totals[group] = totals[group].saturating_add(stake);
+				groups[group].push((who, stake));
+			}
+		}
+		groups
+	}
+}
+
+/// Seeded Fisher–Yates shuffle, reproducible off-chain.
+///
+/// For `i` going from `len - 1` down to `1`, the item at `i` is swapped with the item at
+/// `r(i) % (i + 1)`, where `r(i)` is the little endian `u32` made of the first four bytes of
+/// `blake2_256(seed.encode() ++ (i as u32).encode())`.
+pub fn shuffle<Item, Seed: Encode>(items: &mut [Item], seed: &Seed) {
+	for i in (1..items.len()).rev() {
+		let hash = blake2_256(&(seed, i as u32).encode());
+		let random = u32::from_le_bytes([hash[0], hash[1], hash[2], hash[3]]);
+		items.swap(i, random as usize % (i + 1));
+	}
+}
@@ -1,13 +1,42 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
-use parity_scale_codec::FullCodec;
-use ep_sequencer::{ActiveEraInfo, Forcing};
-use frame_support::traits::{Get, UnixTime};
-use frame_support::BoundedVec;
+pub use election::{
+	by_rank, ElectionError, SequencerElection, StakeGraph, StakeWeightedSampling, Threshold, TopN,
+};
+use ep_sequencer::{
+	ActiveEraInfo, ElectedSequencer, EraRewardPoints, Exposure, Forcing, IndividualExposure,
+	UnlockChunk,
+};
+pub use ep_sequencer::{EraConfig, RewardSequencers, SessionMode};
+use frame_election_provider_support::SortedListProvider;
+use frame_support::{
+	dispatch::{DispatchClass, DispatchResult},
+	ensure,
+	traits::{
+		fungible::{self, BalancedHold, Inspect, MutateHold},
+		tokens::Precision,
+		Get, OnUnbalanced, Randomness, UnixTime, ValidatorRegistration,
+	},
+	weights::Weight,
+	BoundedVec, CloneNoBound, EqNoBound, PartialEqNoBound, RuntimeDebugNoBound,
+};
+pub use grouping::{shuffle, GreedyBalance, GroupAssignment, RankOrder, SnakeDraft};
 pub use pallet::*;
-use sp_core::ConstU32;
+use parity_scale_codec::{Decode, Encode, FullCodec, MaxEncodedLen};
+pub use phragmen::SequentialPhragmen;
+pub use pruning::{MaxCursorLen, PruningProgress, PruningStage};
+pub use registration::{Endpoints, InfoField, Registration, SequencerInfo};
+pub use rewards::{EraRewardSource, Minted, RewardPot};
+use scale_info::TypeInfo;
+pub use slashing::UnappliedSlash;
+pub use sp_npos_elections::BalancingConfig;
+use sp_runtime::{
+	traits::{Saturating, Zero},
+	DispatchError, Perbill,
+};
 use sp_staking::{EraIndex, SessionIndex};
 use sp_std::vec::Vec;
+pub use weights::WeightInfo;
 
 #[cfg(test)]
 mod mock;
@@ -18,7 +47,52 @@ mod tests;
 #[cfg(feature = "runtime-benchmarks")]
 mod benchmarking;
 
-type Sequencer<T> = (<T as frame_system::Config>::AccountId, u128);
+mod api;
+mod delegation;
+mod election;
+mod grouping;
+pub mod migrations;
+mod phragmen;
+mod pruning;
+mod registration;
+mod rewards;
+mod slashing;
+mod stakers;
+pub mod weights;
+
+/// Subject used to draw the seed of the group shuffling.
+const GROUP_SHUFFLE_SUBJECT: &[u8] = b"sequencer_groups";
+
+/// Balance of the currency holding the restaked funds.
+pub type BalanceOf<T> =
+	<<T as Config>::Currency as Inspect<<T as frame_system::Config>::AccountId>>::Balance;
+
+/// Credit of the currency holding the restaked funds.
+pub type CreditOf<T> =
+	fungible::Credit<<T as frame_system::Config>::AccountId, <T as Config>::Currency>;
+
+type Sequencer<T> = ElectedSequencer<<T as frame_system::Config>::AccountId, BalanceOf<T>>;
+
+type SequencerExposure<T> = Exposure<<T as frame_system::Config>::AccountId, BalanceOf<T>>;
+
+/// A group of sequencers formed for an era.
+#[derive(
+	PartialEqNoBound,
+	EqNoBound,
+	CloneNoBound,
+	Encode,
+	Decode,
+	RuntimeDebugNoBound,
+	TypeInfo,
+	MaxEncodedLen,
+)]
+#[scale_info(skip_type_params(T))]
+pub struct SequencerGroup<T: Config> {
+	/// The members of the group, in the order they were assigned.
+	pub members: BoundedVec<T::AccountId, T::MaxSequencers>,
+	/// The sum of the stake of the members, recorded for auditing the group balance.
+	pub total_stake: BalanceOf<T>,
+}
 
 #[frame_support::pallet]
 pub mod pallet {
@@ -32,27 +106,150 @@ pub mod pallet {
 		/// Because this pallet emits events, it depends on the runtime's definition of an event.
 		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
 
-		/// Number of sessions per era.
-		#[pallet::constant]
+		/// The currency holding the restaked funds.
+		type Currency: MutateHold<Self::AccountId, Reason = Self::RuntimeHoldReason>
+			+ BalancedHold<Self::AccountId>;
+
+		/// The overarching hold reason.
+		type RuntimeHoldReason: From<HoldReason>;
+
+		/// Default number of sessions per era.
+		///
+		/// Only used until a config is stored, the live value is in [`CurrentConfig`] and changed
+		/// by [`Pallet::set_config`].
 		type SessionsPerEra: Get<SessionIndex>;
 
-		/// Number of eras to keep in history.
-		#[pallet::constant]
+		/// Default number of eras to keep in history, see [`Config::SessionsPerEra`].
 		type HistoryDepth: Get<u32>;
 
+		/// Number of eras that unbonded funds stay held before they can be withdrawn.
+		#[pallet::constant]
+		type BondingDuration: Get<EraIndex>;
+
+		/// Maximum number of unlocking chunks a staker can have at once.
 		#[pallet::constant]
+		type MaxUnlockingChunks: Get<u32>;
+
+		/// Default number of sequencers an election has to elect at least to succeed, see
+		/// [`Config::SessionsPerEra`].
 		type MinSequencerCount: Get<u32>;
 
+		/// Maximum number of sequencers elected for an era.
+		#[pallet::constant]
+		type MaxSequencers: Get<u32>;
+
+		/// Maximum number of stakers read from [`Config::StakerList`] as candidates of an
+		/// election, in [`SessionMode::Authority`].
+		#[pallet::constant]
+		type MaxCandidates: Get<u32>;
+
+		/// The stakers, sorted by their election weight.
+		///
+		/// Typically a bags list, scored by this pallet.
+		type StakerList: SortedListProvider<Self::AccountId, Score = BalanceOf<Self>>;
+
+		/// Strategy used to elect the sequencers out of the registered validators.
+		///
+		/// [`TopN`] elects the best ranked ones, [`Threshold`] the ones staking enough compared to
+		/// the others and [`StakeWeightedSampling`] samples them by stake.
+		type Election: SequencerElection<Self::AccountId, BalanceOf<Self>>;
+
+		/// Strategy used to assign the elected sequencers to their groups.
+		///
+		/// [`RankOrder`] fills the groups one after the other, [`SnakeDraft`] and
+		/// [`GreedyBalance`] spread the stake evenly over the groups.
+		type GroupAssignment: GroupAssignment<Self::AccountId, BalanceOf<Self>>;
+
+		/// Source of randomness for shuffling the sequencers before they are grouped.
+		type Randomness: Randomness<Self::Hash, BlockNumberFor<Self>>;
+
+		/// The origin which can force eras, set the group layout, report offences and cancel
+		/// deferred slashes.
+		type AdminOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+		/// Number of eras that slashes are deferred by, after they are reported.
+		///
+		/// Set it to zero to apply the slashes right away.
+		#[pallet::constant]
+		type SlashDeferDuration: Get<EraIndex>;
+
+		/// Maximum length of a field of the [`SequencerInfo`] of an operator.
+		#[pallet::constant]
+		type MaxFieldLength: Get<u32>;
+
+		/// Maximum number of RPC or P2P endpoints of an operator.
+		#[pallet::constant]
+		type MaxEndpoints: Get<u32>;
+
+		/// Base deposit for registering an operator.
+		#[pallet::constant]
+		type RegistrationDepositBase: Get<BalanceOf<Self>>;
+
+		/// Deposit for every byte of the encoded [`SequencerInfo`] of an operator.
+		#[pallet::constant]
+		type RegistrationDepositPerByte: Get<BalanceOf<Self>>;
+
+		/// Maximum number of delegators backing the same operator.
+		#[pallet::constant]
+		type MaxDelegators: Get<u32>;
+
+		/// Maximum number of slashes applied at the start of the same era.
+		#[pallet::constant]
+		type MaxUnappliedSlashes: Get<u32>;
+
+		/// Handler for the slashed funds.
+		type Slash: OnUnbalanced<CreditOf<Self>>;
+
+		/// Source of the era rewards, e.g. a pot account or minting.
+		type RewardSource: EraRewardSource<Self::AccountId, BalanceOf<Self>>;
+
+		/// Portion of the era reward split in proportion to the reward points, the rest is split
+		/// in proportion to the stake.
+		#[pallet::constant]
+		type PointsRewardPortion: Get<Perbill>;
+
 		/// Time used for computing era duration.
 		///
 		/// It is guaranteed to start being called from the first `on_finalize`. Thus value at
 		/// genesis is not used.
 		type UnixTime: UnixTime;
+
+		/// What [`SessionManager`] uses the elected sequencers for.
+		///
+		/// With [`SessionMode::Authority`], the registered operators with session keys are the
+		/// candidates of the elections, and the elected ones author the blocks of their era.
+		#[pallet::constant]
+		type SessionMode: Get<SessionMode>;
+
+		/// Checks that an operator has registered its session keys, so it can validate.
+		type ValidatorRegistration: ValidatorRegistration<Self::AccountId>;
+
+		/// The validators used in [`SessionMode::Authority`] when no elected sequencer can
+		/// validate.
+		type Invulnerables: Get<Vec<Self::AccountId>>;
+
+		/// Weight information for the extrinsics and the session-end hook of this pallet.
+		type WeightInfo: WeightInfo;
 	}
 
+	/// The in-code storage version.
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(3);
+
 	#[pallet::pallet]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T>(_);
 
+	/// A reason for the pallet placing a hold on funds.
+	#[pallet::composite_enum]
+	pub enum HoldReason {
+		/// Funds bonded for restaking.
+		#[codec(index = 0)]
+		Restaking,
+		/// Deposit for the registration of an operator.
+		#[codec(index = 1)]
+		Registration,
+	}
+
 	/// The current era index.
 	///
 	/// This is the latest planned era, depending on how the Session pallet queues the validator
@@ -69,7 +266,7 @@ pub mod pallet {
 	#[pallet::getter(fn active_era)]
 	pub type ActiveEra<T> = StorageValue<_, ActiveEraInfo>;
 
-	/// The session index at which the era start for the last [`Config::HistoryDepth`] eras.
+	/// The session index at which the era start for the last `history_depth` eras.
 	///
 	/// Note: This tracks the starting session (i.e. session index when era start being active)
 	/// for the eras in `[CurrentEra - HISTORY_DEPTH, CurrentEra]`.
@@ -82,33 +279,424 @@ pub mod pallet {
 	#[pallet::getter(fn force_era)]
 	pub type ForceEra<T> = StorageValue<_, Forcing, ValueQuery>;
 
+	#[pallet::type_value]
+	pub fn DefaultConfig<T: Config>() -> EraConfig {
+		EraConfig {
+			sessions_per_era: T::SessionsPerEra::get(),
+			history_depth: T::HistoryDepth::get(),
+			min_sequencer_count: T::MinSequencerCount::get(),
+		}
+	}
+
+	/// The era parameters of the current era.
+	///
+	/// Defaults to [`Config::SessionsPerEra`], [`Config::HistoryDepth`] and
+	/// [`Config::MinSequencerCount`], every read of the era parameters goes through it.
+	#[pallet::storage]
+	#[pallet::getter(fn current_config)]
+	pub type CurrentConfig<T> = StorageValue<_, EraConfig, ValueQuery, DefaultConfig<T>>;
+
+	/// The era parameters set by [`Pallet::set_config`], applied when the next era is planned.
+	#[pallet::storage]
+	#[pallet::getter(fn pending_config)]
+	pub type PendingConfig<T> = StorageValue<_, EraConfig>;
+
 	/// Sequencers per group
 	#[pallet::storage]
 	#[pallet::getter(fn sequencers_per_group)]
 	pub type SequencersPerGroup<T> = StorageValue<_, u32, ValueQuery>;
 
+	/// Number of sequencer groups, the election fills `GroupCount * SequencersPerGroup` seats.
+	#[pallet::storage]
+	#[pallet::getter(fn group_count)]
+	pub type GroupCount<T> = StorageValue<_, u32, ValueQuery>;
+
+	/// The sequencers elected for an era, ordered by their rank.
 	#[pallet::storage]
 	#[pallet::getter(fn eras_sequencers)]
 	pub type ErasSequencers<T: Config> = StorageMap<
 		_,
 		Twox64Concat,
 		EraIndex,
-		BoundedVec<Sequencer<T>, ConstU32<{ u32::MAX }>>,
+		BoundedVec<Sequencer<T>, T::MaxSequencers>,
+		ValueQuery,
+	>;
+
+	/// The sequencer groups of an era, along with their total stake.
+	///
+	/// The group index is the position in the vector. Every group holds exactly
+	/// `SequencersPerGroup` sequencers.
+	#[pallet::storage]
+	#[pallet::getter(fn eras_sequencer_groups)]
+	pub type ErasSequencerGroups<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		EraIndex,
+		BoundedVec<SequencerGroup<T>, T::MaxSequencers>,
+		ValueQuery,
+	>;
+
+	/// Elected sequencers of an era which did not fit in a complete group.
+	///
+	/// They are kept as standby, ordered by their rank.
+	#[pallet::storage]
+	#[pallet::getter(fn eras_standby_sequencers)]
+	pub type ErasStandbySequencers<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		EraIndex,
+		BoundedVec<T::AccountId, T::MaxSequencers>,
 		ValueQuery,
 	>;
 
+	/// The seed used to shuffle the sequencers of an era before grouping them.
+	///
+	/// With the elected set of the era, it allows anyone to recompute the groups, see
+	/// [`shuffle`].
+	#[pallet::storage]
+	#[pallet::getter(fn eras_group_seed)]
+	pub type ErasGroupSeed<T: Config> = StorageMap<_, Twox64Concat, EraIndex, T::Hash>;
+
+	/// The total stake the sequencers of an era were elected with.
+	#[pallet::storage]
+	#[pallet::getter(fn eras_total_stake)]
+	pub type ErasTotalStake<T: Config> =
+		StorageMap<_, Twox64Concat, EraIndex, BalanceOf<T>, ValueQuery>;
+
+	/// The reward points earned by the sequencers during an era.
+	#[pallet::storage]
+	#[pallet::unbounded]
+	#[pallet::getter(fn eras_reward_points)]
+	pub type ErasRewardPoints<T: Config> =
+		StorageMap<_, Twox64Concat, EraIndex, EraRewardPoints<T::AccountId>, ValueQuery>;
+
+	/// The reward paid to the sequencers of an era, set once the era has ended.
+	#[pallet::storage]
+	#[pallet::getter(fn eras_sequencer_reward)]
+	pub type ErasSequencerReward<T: Config> = StorageMap<_, Twox64Concat, EraIndex, BalanceOf<T>>;
+
+	/// The start of an era, in milliseconds since the unix epoch.
+	///
+	/// Set in the first `on_finalize` of the era, from [`Config::UnixTime`].
+	#[pallet::storage]
+	#[pallet::getter(fn eras_start_timestamp)]
+	pub type ErasStartTimestamp<T> = StorageMap<_, Twox64Concat, EraIndex, u64>;
+
+	/// How long an era lasted, in milliseconds, set once the era has ended.
+	///
+	/// The wall-clock length of an era drifts from its number of sessions with the missed slots.
+	#[pallet::storage]
+	#[pallet::getter(fn eras_duration)]
+	pub type ErasDuration<T> = StorageMap<_, Twox64Concat, EraIndex, u64>;
+
+	/// Whether a sequencer has claimed its reward for an era.
+	#[pallet::storage]
+	#[pallet::getter(fn claimed_rewards)]
+	pub type ClaimedRewards<T: Config> =
+		StorageDoubleMap<_, Twox64Concat, EraIndex, Twox64Concat, T::AccountId, bool, ValueQuery>;
+
+	/// The amount bonded by a staker, held on [`Config::Currency`].
 	#[pallet::storage]
 	#[pallet::getter(fn restake_data)]
-	pub type RestakeData<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, u128, ValueQuery>;
+	pub type RestakeData<T: Config> =
+		StorageMap<_, Twox64Concat, T::AccountId, BalanceOf<T>, ValueQuery>;
+
+	/// The sum of all the bonds in [`RestakeData`].
+	#[pallet::storage]
+	#[pallet::getter(fn total_restake)]
+	pub type TotalRestake<T: Config> = StorageValue<_, BalanceOf<T>, ValueQuery>;
+
+	/// The number of stakers in [`RestakeData`].
+	#[pallet::storage]
+	#[pallet::getter(fn restaker_count)]
+	pub type RestakerCount<T> = StorageValue<_, u32, ValueQuery>;
+
+	/// Funds of a staker being unbonded, they are still held but no longer count as stake.
+	#[pallet::storage]
+	#[pallet::getter(fn unlocking)]
+	pub type Unlocking<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		T::AccountId,
+		BoundedVec<UnlockChunk<BalanceOf<T>>, T::MaxUnlockingChunks>,
+		ValueQuery,
+	>;
+
+	/// The registered operators, only they are eligible in the elections.
+	#[pallet::storage]
+	#[pallet::getter(fn registrations)]
+	pub type Registrations<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, Registration<T>>;
+
+	/// The funds delegated to an operator, by operator and delegator.
+	///
+	/// They are held on [`Config::Currency`], like the bonded funds.
+	#[pallet::storage]
+	#[pallet::getter(fn delegations)]
+	pub type Delegations<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		T::AccountId,
+		Twox64Concat,
+		T::AccountId,
+		BalanceOf<T>,
+		ValueQuery,
+	>;
+
+	/// The total amount delegated to an operator.
+	#[pallet::storage]
+	#[pallet::getter(fn total_delegated)]
+	pub type TotalDelegated<T: Config> =
+		StorageMap<_, Twox64Concat, T::AccountId, BalanceOf<T>, ValueQuery>;
+
+	/// The number of delegators backing an operator, at most [`Config::MaxDelegators`].
+	#[pallet::storage]
+	#[pallet::getter(fn delegator_count)]
+	pub type DelegatorCount<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, u32, ValueQuery>;
+
+	/// The commission an operator takes on the rewards, before they are split with its
+	/// delegators, set when it registers.
+	#[pallet::storage]
+	#[pallet::getter(fn commissions)]
+	pub type Commissions<T: Config> =
+		StorageMap<_, Twox64Concat, T::AccountId, Perbill, ValueQuery>;
+
+	/// The stake backing the sequencers elected for an era, by era and sequencer.
+	#[pallet::storage]
+	#[pallet::unbounded]
+	#[pallet::getter(fn eras_exposures)]
+	pub type ErasExposures<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		EraIndex,
+		Twox64Concat,
+		T::AccountId,
+		Exposure<T::AccountId, BalanceOf<T>>,
+		ValueQuery,
+	>;
+
+	/// The commission of the sequencers elected for an era, by era and sequencer.
+	#[pallet::storage]
+	#[pallet::getter(fn eras_commissions)]
+	pub type ErasCommissions<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		EraIndex,
+		Twox64Concat,
+		T::AccountId,
+		Perbill,
+		ValueQuery,
+	>;
+
+	/// Slashes waiting to be applied, by the era they are applied at.
+	#[pallet::storage]
+	#[pallet::getter(fn unapplied_slashes)]
+	pub type UnappliedSlashes<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		EraIndex,
+		BoundedVec<UnappliedSlash<T>, T::MaxUnappliedSlashes>,
+		ValueQuery,
+	>;
+
+	/// The highest fraction a sequencer has been slashed for the offences committed in an era.
+	#[pallet::storage]
+	#[pallet::getter(fn eras_slash_fractions)]
+	pub type ErasSlashFractions<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		EraIndex,
+		Twox64Concat,
+		T::AccountId,
+		Perbill,
+		ValueQuery,
+	>;
+
+	/// The amount slashed from an operator or a delegator for the offences committed in an era.
+	#[pallet::storage]
+	#[pallet::getter(fn eras_slashes)]
+	pub type ErasSlashes<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		EraIndex,
+		Twox64Concat,
+		T::AccountId,
+		BalanceOf<T>,
+		ValueQuery,
+	>;
+
+	/// Where the pruning of the eras out of history is at.
+	#[pallet::storage]
+	#[pallet::getter(fn history_pruning)]
+	pub type HistoryPruning<T> = StorageValue<_, PruningProgress<T>, ValueQuery>;
+
+	#[pallet::genesis_config]
+	#[derive(frame_support::DefaultNoBound)]
+	pub struct GenesisConfig<T: Config> {
+		/// The era parameters to start with, the constants of [`Config`] by default.
+		pub era_config: Option<EraConfig>,
+		/// The operators bonding at genesis, with their bond.
+		///
+		/// They are registered with an empty [`SequencerInfo`], holding the registration deposit
+		/// on top of the bond.
+		pub stakers: Vec<(T::AccountId, BalanceOf<T>)>,
+		/// Number of sequencers per group.
+		pub sequencers_per_group: u32,
+		/// Number of sequencer groups.
+		pub group_count: u32,
+		/// The mode of era forcing.
+		pub force_era: Forcing,
+		/// The sequencers of era 0, elected at the first session if not set.
+		///
+		/// They must be among the `stakers`.
+		pub sequencers: Option<Vec<T::AccountId>>,
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> BuildGenesisConfig for GenesisConfig<T> {
+		fn build(&self) {
+			if let Some(config) = self.era_config {
+				Pallet::<T>::ensure_valid_config(&config).expect("invalid genesis era config");
+				CurrentConfig::<T>::put(config);
+			}
+			SequencersPerGroup::<T>::put(self.sequencers_per_group);
+			GroupCount::<T>::put(self.group_count);
+			ForceEra::<T>::put(self.force_era);
+
+			for (who, bond) in &self.stakers {
+				Pallet::<T>::do_bond(who.clone(), *bond).expect("genesis staker can not bond");
+				let info = SequencerInfo {
+					display_name: Default::default(),
+					rpc_endpoints: Default::default(),
+					p2p_endpoints: Default::default(),
+					contact: Default::default(),
+				};
+				Pallet::<T>::do_register_sequencer(who.clone(), info, Perbill::zero())
+					.expect("genesis staker can not register");
+			}
+
+			if let Some(sequencers) = &self.sequencers {
+				Pallet::<T>::plan_genesis_era(sequencers);
+			}
+		}
+	}
 
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
-	pub enum Event<T: Config> {}
+	pub enum Event<T: Config> {
+		/// A new era has been planned, it becomes active at `start_session`.
+		EraPlanned { era: EraIndex, start_session: SessionIndex },
+		/// An era became active, `timestamp` being its start in milliseconds since the unix
+		/// epoch.
+		EraStarted { era: EraIndex, timestamp: u64 },
+		/// The sequencers of an era have been elected.
+		SequencersElected { era: EraIndex, count: u32, total_stake: BalanceOf<T> },
+		/// The elected sequencers of an era have been grouped, `standby` of them did not fit in a
+		/// group.
+		GroupsFormed { era: EraIndex, groups: u32, standby: u32 },
+		/// An account has bonded `amount` more.
+		StakeDeposited { who: T::AccountId, amount: BalanceOf<T> },
+		/// An account has unbonded `amount`, which can be withdrawn from `era` on.
+		Unbonded { who: T::AccountId, amount: BalanceOf<T>, era: EraIndex },
+		/// An account has withdrawn `amount` of unbonded funds.
+		Withdrawn { who: T::AccountId, amount: BalanceOf<T> },
+		/// An offence of `sequencer` in `era` has been reported, `amount` is slashed at the start
+		/// of `apply_era` on top of what was already slashed for `era`.
+		SlashReported {
+			sequencer: T::AccountId,
+			era: EraIndex,
+			fraction: Perbill,
+			amount: BalanceOf<T>,
+			apply_era: EraIndex,
+		},
+		/// A deferred slash has been cancelled.
+		SlashCancelled { sequencer: T::AccountId, era: EraIndex, apply_era: EraIndex },
+		/// `who`, the operator `sequencer` or one of its delegators, has been slashed for an
+		/// offence committed in `era`.
+		Slashed { who: T::AccountId, sequencer: T::AccountId, era: EraIndex, amount: BalanceOf<T> },
+		/// An era has ended, `reward` is to be paid out to its sequencers.
+		EraPaid { era: EraIndex, reward: BalanceOf<T> },
+		/// `who`, the operator `sequencer` or one of its delegators, has been paid its reward
+		/// for `era`.
+		Rewarded { who: T::AccountId, sequencer: T::AccountId, era: EraIndex, amount: BalanceOf<T> },
+		/// `delegator` has delegated `amount` to `operator`.
+		Delegated { delegator: T::AccountId, operator: T::AccountId, amount: BalanceOf<T> },
+		/// `delegator` has undelegated `amount` from `operator`, it can be withdrawn at `era`.
+		Undelegated {
+			delegator: T::AccountId,
+			operator: T::AccountId,
+			amount: BalanceOf<T>,
+			era: EraIndex,
+		},
+		/// An operator has registered with `commission`, holding `deposit`.
+		SequencerRegistered { operator: T::AccountId, deposit: BalanceOf<T>, commission: Perbill },
+		/// An operator has updated its info, the deposit held is now `deposit`.
+		SequencerUpdated { operator: T::AccountId, deposit: BalanceOf<T> },
+		/// An operator has deregistered, its deposit is released.
+		SequencerDeregistered { operator: T::AccountId },
+		/// The number of sequencers per group has been set.
+		SequencersPerGroupSet { sequencers_per_group: u32 },
+		/// The number of sequencer groups has been set.
+		GroupCountSet { group_count: u32 },
+		/// The mode of era forcing has changed.
+		ForcingChanged { mode: Forcing },
+		/// New era parameters have been set, they apply from the next planned era.
+		ConfigSet { config: EraConfig },
+		/// The era parameters set last apply from `era` on.
+		ConfigApplied { era: EraIndex, config: EraConfig },
+		/// The election planned at `session` failed, the sequencers of the current era are kept
+		/// and the election is retried at the next session.
+		ElectionFailed { session: SessionIndex, error: ElectionError },
+		/// The groups of the era planned at `session` could not be stored, the sequencers of the
+		/// current era are kept and the election is retried at the next session.
+		GroupingFailed { session: SessionIndex, error: DispatchError },
+	}
 
 	#[pallet::error]
 	pub enum Error<T> {
 		NoneValue,
 		StorageOverflow,
+		/// The account has already bonded, use `bond_extra`.
+		AlreadyBonded,
+		/// The account has not bonded yet, use `bond`.
+		NotBonded,
+		/// The amount to bond must not be zero.
+		InsufficientBond,
+		/// Can not schedule more unlock chunks, withdraw the unbonded funds first.
+		NoMoreChunks,
+		/// The era is not active yet or no longer in history.
+		InvalidEra,
+		/// The account was not elected in the era.
+		NotElected,
+		/// Too many slashes are already queued for the era.
+		TooManySlashes,
+		/// No slash to cancel was given.
+		EmptyTargets,
+		/// The slash indices are not sorted or contain duplicates.
+		NotSortedAndUnique,
+		/// The slash index is out of bounds.
+		InvalidSlashIndex,
+		/// The reward of the era has already been claimed.
+		AlreadyClaimed,
+		/// An operator can not delegate to itself.
+		SelfDelegation,
+		/// The account is not a registered sequencer operator.
+		NotOperator,
+		/// The operator is already backed by [`Config::MaxDelegators`] delegators.
+		TooManyDelegators,
+		/// Nothing is delegated to the operator.
+		NotDelegated,
+		/// The operator is already registered.
+		AlreadyRegistered,
+		/// The operator is not registered.
+		NotRegistered,
+		/// An era must last at least one session.
+		ZeroSessionsPerEra,
+		/// At least one era must be kept in history.
+		ZeroHistoryDepth,
+		/// The minimum number of sequencers exceeds [`Config::MaxSequencers`].
+		MinSequencerCountTooHigh,
+		/// More groups were formed than [`Config::MaxSequencers`].
+		TooManyGroups,
 	}
 
 	#[pallet::hooks]
@@ -119,153 +707,589 @@ pub mod pallet {
 				if active_era.start.is_none() {
 					let now_as_millis_u64 = T::UnixTime::now().as_millis().saturated_into::<u64>();
 					active_era.start = Some(now_as_millis_u64);
-					// This write only ever happens once, we don't include it in the weight in
-					// general
+					Self::deposit_event(Event::<T>::EraStarted {
+						era: active_era.index,
+						timestamp: now_as_millis_u64,
+					});
+					// These writes only ever happen once per era, we don't include them in the
+					// weight in general
+					ErasStartTimestamp::<T>::insert(active_era.index, now_as_millis_u64);
 					ActiveEra::<T>::put(active_era);
 				}
 			}
 			// `on_finalize` weight is tracked in `on_initialize`
 		}
+
+		fn on_idle(_n: BlockNumberFor<T>, remaining_weight: Weight) -> Weight {
+			Self::prune_history(remaining_weight)
+		}
+
+		fn integrity_test() {
+			// The default config applies until one is stored, it must be valid as well.
+			assert!(
+				Self::ensure_valid_config(&DefaultConfig::<T>::get()).is_ok(),
+				"The default era config is invalid, no era could be planned or elected",
+			);
+		}
 	}
 
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
-		// Temp Set RestakeData
-		#[pallet::weight({0})]
-		#[pallet::call_index(0)]
-		pub fn deposit(
-			_origin: OriginFor<T>,
-			account_id: T::AccountId,
-			amount: u128,
+		/// Bond `amount` of the caller's funds for restaking.
+		///
+		/// The funds are held on [`Config::Currency`] and count toward the next elections.
+		#[pallet::weight(T::WeightInfo::bond())]
+		#[pallet::call_index(19)]
+		pub fn bond(origin: OriginFor<T>, amount: BalanceOf<T>) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			ensure!(RestakeData::<T>::get(&who).is_zero(), Error::<T>::AlreadyBonded);
+			Self::do_bond(who, amount)?;
+			Ok(().into())
+		}
+
+		/// Bond `amount` more of the caller's funds, on top of what is already bonded.
+		#[pallet::weight(T::WeightInfo::bond_extra())]
+		#[pallet::call_index(6)]
+		pub fn bond_extra(
+			origin: OriginFor<T>,
+			amount: BalanceOf<T>,
 		) -> DispatchResultWithPostInfo {
-			RestakeData::<T>::insert(&account_id, amount);
+			let who = ensure_signed(origin)?;
+			ensure!(!RestakeData::<T>::get(&who).is_zero(), Error::<T>::NotBonded);
+			Self::do_bond(who, amount)?;
 			Ok(().into())
 		}
 
+		/// Unbond `amount` of the caller's bonded funds.
+		///
+		/// The funds stop counting toward the elections right away. They can be withdrawn with
+		/// `withdraw_unbonded` once [`Config::BondingDuration`] eras have passed.
+		#[pallet::weight(T::WeightInfo::unbond())]
+		#[pallet::call_index(7)]
+		pub fn unbond(origin: OriginFor<T>, amount: BalanceOf<T>) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			let bonded = RestakeData::<T>::get(&who);
+			ensure!(!bonded.is_zero(), Error::<T>::NotBonded);
+
+			let amount = amount.min(bonded);
+			ensure!(!amount.is_zero(), Error::<T>::InsufficientBond);
+
+			let era = Self::schedule_unlock(&who, amount)?;
+			Self::set_bonded(&who, bonded.saturating_sub(amount));
+
+			Self::deposit_event(Event::<T>::Unbonded { who, amount, era });
+			Ok(().into())
+		}
+
+		/// Release the caller's unbonded funds whose bonding duration is over.
+		#[pallet::weight(T::WeightInfo::withdraw_unbonded())]
+		#[pallet::call_index(8)]
+		pub fn withdraw_unbonded(origin: OriginFor<T>) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			let current_era = Self::current_era().unwrap_or(0);
+
+			let mut withdrawn = BalanceOf::<T>::zero();
+			Unlocking::<T>::mutate_exists(&who, |maybe_chunks| {
+				if let Some(chunks) = maybe_chunks {
+					chunks.retain(|chunk| {
+						if chunk.era <= current_era {
+							withdrawn = withdrawn.saturating_add(chunk.value);
+							false
+						} else {
+							true
+						}
+					});
+					if chunks.is_empty() {
+						*maybe_chunks = None;
+					}
+				}
+			});
+
+			if !withdrawn.is_zero() {
+				T::Currency::release(
+					&HoldReason::Restaking.into(),
+					&who,
+					withdrawn,
+					Precision::BestEffort,
+				)?;
+				Self::deposit_event(Event::<T>::Withdrawn { who, amount: withdrawn });
+			}
+			Ok(().into())
+		}
 
 		// Set SequencersPerGroup
-		#[pallet::weight({1})]
+		#[pallet::weight(T::WeightInfo::set_sequencers_per_group())]
 		#[pallet::call_index(1)]
 		pub fn set_sequencers_per_group(
 			origin: OriginFor<T>,
 			sequencers_per_group: u32,
 		) -> DispatchResultWithPostInfo {
-			ensure_root(origin)?;
+			T::AdminOrigin::ensure_origin(origin)?;
 			SequencersPerGroup::<T>::put(sequencers_per_group);
+			Self::deposit_event(Event::<T>::SequencersPerGroupSet { sequencers_per_group });
+			Ok(().into())
+		}
+
+		// Set GroupCount
+		#[pallet::weight(T::WeightInfo::set_group_count())]
+		#[pallet::call_index(2)]
+		pub fn set_group_count(
+			origin: OriginFor<T>,
+			group_count: u32,
+		) -> DispatchResultWithPostInfo {
+			T::AdminOrigin::ensure_origin(origin)?;
+			GroupCount::<T>::put(group_count);
+			Self::deposit_event(Event::<T>::GroupCountSet { group_count });
+			Ok(().into())
+		}
+
+		/// Force a new era at the end of the next session, then go back to normal.
+		///
+		/// If the election fails, a new era is forced again at the end of every session until
+		/// an election succeeds.
+		///
+		/// The dispatch origin must be [`Config::AdminOrigin`].
+		#[pallet::weight(T::WeightInfo::force_new_era())]
+		#[pallet::call_index(3)]
+		pub fn force_new_era(origin: OriginFor<T>) -> DispatchResultWithPostInfo {
+			T::AdminOrigin::ensure_origin(origin)?;
+			Self::set_force_era(Forcing::ForceNew);
+			Ok(().into())
+		}
+
+		/// Stop planning new eras indefinitely, the current sequencers are kept.
+		///
+		/// The dispatch origin must be [`Config::AdminOrigin`].
+		#[pallet::weight(T::WeightInfo::force_no_eras())]
+		#[pallet::call_index(4)]
+		pub fn force_no_eras(origin: OriginFor<T>) -> DispatchResultWithPostInfo {
+			T::AdminOrigin::ensure_origin(origin)?;
+			Self::set_force_era(Forcing::ForceNone);
+			Ok(().into())
+		}
+
+		/// Force a new era at the end of every session, indefinitely.
+		///
+		/// The dispatch origin must be [`Config::AdminOrigin`].
+		#[pallet::weight(T::WeightInfo::force_new_era_always())]
+		#[pallet::call_index(5)]
+		pub fn force_new_era_always(origin: OriginFor<T>) -> DispatchResultWithPostInfo {
+			T::AdminOrigin::ensure_origin(origin)?;
+			Self::set_force_era(Forcing::ForceAlways);
+			Ok(().into())
+		}
+
+		/// Report an offence of `sequencer` in `era`, slashing `fraction` of its stake.
+		///
+		/// The slash is deferred by [`Config::SlashDeferDuration`] eras.
+		///
+		/// The dispatch origin must be [`Config::AdminOrigin`].
+		#[pallet::weight(T::WeightInfo::report_offence(T::MaxDelegators::get()))]
+		#[pallet::call_index(9)]
+		pub fn report_offence(
+			origin: OriginFor<T>,
+			era: EraIndex,
+			sequencer: T::AccountId,
+			fraction: Perbill,
+		) -> DispatchResultWithPostInfo {
+			T::AdminOrigin::ensure_origin(origin)?;
+			Self::do_report_offence(era, sequencer, fraction)?;
+			Ok(().into())
+		}
+
+		/// Cancel the slashes deferred to `era` at `slash_indices`.
+		///
+		/// The dispatch origin must be [`Config::AdminOrigin`].
+		#[pallet::weight(T::WeightInfo::cancel_deferred_slash(T::MaxUnappliedSlashes::get()))]
+		#[pallet::call_index(10)]
+		pub fn cancel_deferred_slash(
+			origin: OriginFor<T>,
+			era: EraIndex,
+			slash_indices: Vec<u32>,
+		) -> DispatchResultWithPostInfo {
+			T::AdminOrigin::ensure_origin(origin)?;
+			Self::do_cancel_deferred_slash(era, slash_indices)?;
+			Ok(().into())
+		}
+
+		/// Pay `sequencer` its share of the reward of `era`.
+		///
+		/// Any account can trigger the payout, once `era` has ended and as long as it is at most
+		/// `history_depth` eras older than the active era.
+		#[pallet::weight(T::WeightInfo::payout_era(T::MaxDelegators::get()))]
+		#[pallet::call_index(11)]
+		pub fn payout_era(
+			origin: OriginFor<T>,
+			era: EraIndex,
+			sequencer: T::AccountId,
+		) -> DispatchResultWithPostInfo {
+			ensure_signed(origin)?;
+			Self::do_payout_era(era, sequencer)?;
+			Ok(().into())
+		}
+
+		/// Delegate `amount` of the caller's funds to `operator`.
+		///
+		/// The funds are held on [`Config::Currency`] and count toward the election weight of
+		/// `operator`.
+		#[pallet::weight(T::WeightInfo::delegate())]
+		#[pallet::call_index(12)]
+		pub fn delegate(
+			origin: OriginFor<T>,
+			operator: T::AccountId,
+			amount: BalanceOf<T>,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			Self::do_delegate(who, operator, amount)?;
+			Ok(().into())
+		}
+
+		/// Undelegate `amount` of the funds the caller delegated to `operator`.
+		///
+		/// They can be withdrawn with `withdraw_unbonded` once [`Config::BondingDuration`] eras
+		/// have passed.
+		#[pallet::weight(T::WeightInfo::undelegate())]
+		#[pallet::call_index(13)]
+		pub fn undelegate(
+			origin: OriginFor<T>,
+			operator: T::AccountId,
+			amount: BalanceOf<T>,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			Self::do_undelegate(who, operator, amount)?;
+			Ok(().into())
+		}
+
+		/// Register the caller as a sequencer operator described by `info`, taking `commission`
+		/// on its rewards before they are split with its delegators.
+		///
+		/// A deposit depending on the size of `info` is held, see
+		/// [`Pallet::registration_deposit`]. The commission applies from the next election on.
+		#[pallet::weight(T::WeightInfo::register_sequencer())]
+		#[pallet::call_index(15)]
+		pub fn register_sequencer(
+			origin: OriginFor<T>,
+			info: SequencerInfo<T>,
+			commission: Perbill,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			Self::do_register_sequencer(who, info, commission)?;
+			Ok(().into())
+		}
+
+		/// Replace the info of the caller, the deposit is adjusted to the new info.
+		#[pallet::weight(T::WeightInfo::update_sequencer())]
+		#[pallet::call_index(16)]
+		pub fn update_sequencer(
+			origin: OriginFor<T>,
+			info: SequencerInfo<T>,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			Self::do_update_sequencer(who, info)?;
+			Ok(().into())
+		}
+
+		/// Deregister the caller, releasing its deposit.
+		#[pallet::weight(T::WeightInfo::deregister_sequencer())]
+		#[pallet::call_index(17)]
+		pub fn deregister_sequencer(origin: OriginFor<T>) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			Self::do_deregister_sequencer(who)?;
+			Ok(().into())
+		}
+
+		/// Set the era parameters, they apply from the next planned era.
+		///
+		/// The dispatch origin must be [`Config::AdminOrigin`].
+		#[pallet::weight(T::WeightInfo::set_config())]
+		#[pallet::call_index(18)]
+		pub fn set_config(origin: OriginFor<T>, config: EraConfig) -> DispatchResultWithPostInfo {
+			T::AdminOrigin::ensure_origin(origin)?;
+			Self::ensure_valid_config(&config)?;
+			PendingConfig::<T>::put(config);
+			Self::deposit_event(Event::<T>::ConfigSet { config });
 			Ok(().into())
 		}
 	}
 }
 
 impl<T: Config> Pallet<T> {
-	/// Clear all era information for given era.
-	pub(crate) fn clear_era_information(era_index: EraIndex) {
-		ErasStartSessionIndex::<T>::remove(era_index);
+	/// Set the mode of era forcing.
+	pub(crate) fn set_force_era(mode: Forcing) {
+		ForceEra::<T>::put(mode);
+		Self::deposit_event(Event::<T>::ForcingChanged { mode });
+	}
+
+	/// Check that `config` allows eras to be planned and elections to succeed.
+	pub(crate) fn ensure_valid_config(config: &EraConfig) -> DispatchResult {
+		ensure!(config.sessions_per_era > 0, Error::<T>::ZeroSessionsPerEra);
+		ensure!(config.history_depth > 0, Error::<T>::ZeroHistoryDepth);
+		ensure!(
+			config.min_sequencer_count <= T::MaxSequencers::get(),
+			Error::<T>::MinSequencerCountTooHigh
+		);
+		Ok(())
+	}
+
+	/// The era parameters the next planned era will have.
+	pub fn next_config() -> EraConfig {
+		Self::pending_config().unwrap_or_else(Self::current_config)
+	}
+
+	/// Hold `amount` of `who`'s funds and add it to their bond.
+	fn do_bond(who: T::AccountId, amount: BalanceOf<T>) -> DispatchResult {
+		ensure!(!amount.is_zero(), Error::<T>::InsufficientBond);
+		T::Currency::hold(&HoldReason::Restaking.into(), &who, amount)?;
+		Self::set_bonded(&who, Self::restake_data(&who).saturating_add(amount));
+		Self::deposit_event(Event::<T>::StakeDeposited { who, amount });
+		Ok(())
+	}
+
+	/// Move `amount` of `who`'s funds to the unbonding queue, returning the era they can be
+	/// withdrawn at.
+	pub(crate) fn schedule_unlock(
+		who: &T::AccountId,
+		amount: BalanceOf<T>,
+	) -> Result<EraIndex, DispatchError> {
+		let era = Self::current_era().unwrap_or(0).saturating_add(T::BondingDuration::get());
+		Unlocking::<T>::try_mutate(who, |chunks| match chunks.last_mut() {
+			Some(chunk) if chunk.era == era => {
+				chunk.value = chunk.value.saturating_add(amount);
+				Ok(())
+			},
+			_ => chunks
+				.try_push(UnlockChunk { value: amount, era })
+				.map_err(|_| Error::<T>::NoMoreChunks),
+		})?;
+		Ok(era)
 	}
 
+	/// Plan a new era starting at `start_session_index`, if the election succeeds.
+	///
+	/// A failed election, or groups which can not be stored, plan nothing: the current era goes
+	/// on with its sequencers, and since neither the era length nor the forcing mode changed, the
+	/// election is retried at the next session. The first era is only planned once an election
+	/// succeeds.
 	fn try_trigger_new_era(
 		start_session_index: SessionIndex,
 		validators: &Vec<T::AccountId>,
-	) -> Option<BoundedVec<Sequencer<T>, ConstU32<{ u32::MAX }>>> {
-		match CurrentEra::<T>::get() {
-			None => {
-				CurrentEra::<T>::put(0);
-				ErasStartSessionIndex::<T>::insert(&0, &start_session_index);
-				log::info!("Updated current_era to: 0");
+	) -> Option<BoundedVec<Sequencer<T>, T::MaxSequencers>> {
+		match Self::elect_sequencers(validators) {
+			Ok((sequencers, exposures)) =>
+				match Self::trigger_new_era(start_session_index, sequencers, exposures) {
+					Ok(sequencers) => Some(sequencers),
+					Err(error) => {
+						log::error!(
+							"Sequencer groups not stored: {:?}, retrying at the next session",
+							error
+						);
+						Self::deposit_event(Event::<T>::GroupingFailed {
+							session: start_session_index,
+							error,
+						});
+						None
+					},
+				},
+			Err(error) => {
+				log::warn!("Sequencer election failed: {:?}, retrying at the next session", error);
+				Self::deposit_event(Event::<T>::ElectionFailed {
+					session: start_session_index,
+					error,
+				});
+				None
 			},
-			_ => (),
 		}
-
-		Self::trigger_new_era(start_session_index, validators)
 	}
 
+	/// Plan a new era starting at `start_session_index` with the elected `sequencers`.
+	///
+	/// The groups are stored first: if they can not be, nothing else is and the era is not
+	/// planned.
 	fn trigger_new_era(
 		start_session_index: SessionIndex,
-		validators: &Vec<T::AccountId>,
-	) -> Option<BoundedVec<Sequencer<T>, ConstU32<{ u32::MAX }>>> {
-		let new_planned_era = CurrentEra::<T>::mutate(|s| {
-			*s = Some(s.map(|s| s + 1).unwrap_or(0));
-			s.unwrap()
-		});
+		sequencers: BoundedVec<Sequencer<T>, T::MaxSequencers>,
+		exposures: Vec<(T::AccountId, SequencerExposure<T>)>,
+	) -> Result<BoundedVec<Sequencer<T>, T::MaxSequencers>, DispatchError> {
+		let new_planned_era = Self::current_era().map_or(0, |era| era.saturating_add(1));
+
+		let (seed, _) = T::Randomness::random(&(GROUP_SHUFFLE_SUBJECT, new_planned_era).encode());
+		let (groups, standby) = Self::form_groups(&sequencers, &seed);
+		let (group_count, standby_count) = (groups.len() as u32, standby.len() as u32);
+		Self::register_weight(T::WeightInfo::form_groups(group_count));
+		EraInfo::<T>::set_groups(new_planned_era, groups, standby, seed)?;
 
+		CurrentEra::<T>::put(new_planned_era);
 		ErasStartSessionIndex::<T>::insert(&new_planned_era, &start_session_index);
 
-		if let Some(old_era) = new_planned_era.checked_sub(T::HistoryDepth::get()) {
-			Self::clear_era_information(old_era);
+		if let Some(config) = PendingConfig::<T>::take() {
+			CurrentConfig::<T>::put(config);
+			Self::deposit_event(Event::<T>::ConfigApplied { era: new_planned_era, config });
 		}
 
-		// TODO: Order the restake data by stake amount descending and take k*N validators with the highest stakes as sequencers.
+		// The eras falling out of history are pruned in `on_idle`.
 
-		// TODO: grouping sequencers into k groups, and each group has n sequencers
+		Self::register_weight(T::WeightInfo::set_exposures(exposures.len() as u32));
+		let total_stake = sequencers
+			.iter()
+			.fold(Zero::zero(), |total: BalanceOf<T>, s| total.saturating_add(s.stake));
 
+		EraInfo::<T>::set_sequencer(new_planned_era, sequencers.clone());
+		EraInfo::<T>::set_exposures(new_planned_era, exposures);
 
-		let min_sequencers = T::MinSequencerCount::get() as usize;
-		let (total_stake, num_stakers) = RestakeData::<T>::iter()
-			.fold((0u128, 0usize), |(total_stake, count), (_, stake)| {
-				(total_stake + stake, count + 1)
-			});
+		Self::deposit_event(Event::<T>::EraPlanned {
+			era: new_planned_era,
+			start_session: start_session_index,
+		});
+		Self::deposit_event(Event::<T>::SequencersElected {
+			era: new_planned_era,
+			count: sequencers.len() as u32,
+			total_stake,
+		});
+		Self::deposit_event(Event::<T>::GroupsFormed {
+			era: new_planned_era,
+			groups: group_count,
+			standby: standby_count,
+		});
 
-		// change RestakeData into vec and sort by stake amount descending
-		let restake_data = RestakeData::<T>::iter()
-			.map(|(k, v)| (k, v))
-			.collect::<Vec<_>>();
+		// The era `ForceNew` asked for has been triggered.
+		if Self::force_era() == Forcing::ForceNew {
+			Self::set_force_era(Forcing::NotForcing);
+		}
 
-		// let sorted_restake_data = restake_data
-		// 	.into_iter()
-		// 	.sorted_by(|a, b| b.1.cmp(&a.1))
-		// 	.collect::<Vec<_>>();
+		log::info!("New era #{} has started at session {}", new_planned_era, start_session_index);
 
-		let average_stake = if num_stakers > 0 { total_stake / num_stakers as u128 } else { 0 };
+		Ok(sequencers)
+	}
 
-		// 2. filter amount greater than avg's 2/3 validators
-		let two_thirds_average = {
-			let temp = average_stake * 2;
-			if temp >= 3 {
-				temp / 3
-			} else {
-				1
-			}
-		};
-		let mut sequencers = Vec::new();
+	/// Register `weight` as consumed in the current block.
+	///
+	/// The elections and the grouping run in the session-end hook, called from the
+	/// `on_initialize` of the session pallet, so their weight is noted as mandatory.
+	pub(crate) fn register_weight(weight: Weight) {
+		frame_system::Pallet::<T>::register_extra_weight_unchecked(
+			weight,
+			DispatchClass::Mandatory,
+		);
+	}
 
-		for validator in validators {
-			let stake = RestakeData::<T>::get(validator);
-			if stake >= two_thirds_average {
-				sequencers.push((validator.clone(), stake));
-			}
-		}
+	/// Number of seats to fill in an election.
+	///
+	/// This is `GroupCount * SequencersPerGroup`, but never less than `min_sequencer_count` nor
+	/// more than `MaxSequencers`.
+	pub(crate) fn sequencer_seats(min_sequencer_count: u32) -> u32 {
+		Self::group_count()
+			.saturating_mul(Self::sequencers_per_group())
+			.max(min_sequencer_count)
+			.min(T::MaxSequencers::get())
+	}
 
-		// 3. if sequencer amount less than min_sequencers，add more validators to sequencers until sequencers.len() >= min_sequencers
-		if sequencers.len() < min_sequencers {
-			for validator in validators {
-				if !sequencers.iter().any(|(v, _)| v == validator) {
-					let stake = RestakeData::<T>::get(validator);
-					sequencers.push((validator.clone(), stake));
-					if sequencers.len() >= min_sequencers {
-						break;
-					}
-				}
-			}
+	/// Elect the sequencers out of the registered `validators` with [`Config::Election`], for
+	/// [`Pallet::sequencer_seats`] seats, along with the stake backing them.
+	///
+	/// The election runs on the own stake of the validators and the delegations to them. The
+	/// elected sequencers are ordered by their backing stake descending, ties are broken by the
+	/// ascending order of the account id, so the ranking only depends on the validator set and
+	/// the stake table.
+	///
+	/// The election fails if it elects less than the `min_sequencer_count` of the next era
+	/// sequencers.
+	pub(crate) fn elect_sequencers(
+		validators: &Vec<T::AccountId>,
+	) -> Result<
+		(BoundedVec<Sequencer<T>, T::MaxSequencers>, Vec<(T::AccountId, SequencerExposure<T>)>),
+		ElectionError,
+	> {
+		let mut candidates = validators
+			.iter()
+			.filter(|validator| Registrations::<T>::contains_key(validator))
+			.map(|validator| (validator.clone(), Self::restake_data(validator)))
+			.collect::<Vec<_>>();
+		candidates.sort_by(|a, b| a.0.cmp(&b.0));
+		candidates.dedup_by(|a, b| a.0 == b.0);
+
+		let delegations = candidates
+			.iter()
+			.flat_map(|(operator, _)| {
+				Delegations::<T>::iter_prefix(operator)
+					.map(move |(delegator, amount)| (delegator, operator.clone(), amount))
+			})
+			.collect::<Vec<_>>();
+		Self::register_weight(T::WeightInfo::elect_sequencers(
+			validators.len() as u32,
+			delegations.len() as u32,
+		));
+
+		let min_sequencer_count = Self::next_config().min_sequencer_count;
+		let mut exposures = T::Election::elect_exposures::<T::MaxSequencers>(
+			StakeGraph { candidates, delegations },
+			Self::sequencer_seats(min_sequencer_count),
+			min_sequencer_count,
+		)?
+		.into_inner();
+		if (exposures.len() as u32) < min_sequencer_count {
+			return Err(ElectionError::NotEnoughWinners);
 		}
+		exposures.sort_by(|a, b| by_rank(&(&a.0, a.1.total), &(&b.0, b.1.total)));
 
-		let bounded_sequencers: BoundedVec<Sequencer<T>, ConstU32<{ u32::MAX }>> =
-			sequencers.try_into().expect("too many validators");
+		let sequencers = exposures
+			.iter()
+			.enumerate()
+			.map(|(rank, (who, exposure))| ElectedSequencer {
+				who: who.clone(),
+				stake: exposure.total,
+				rank: rank as u32,
+			})
+			.collect::<Vec<_>>();
+		// As many sequencers as exposures, which are bounded by `MaxSequencers`.
+		let sequencers = BoundedVec::truncate_from(sequencers);
+		Ok((sequencers, exposures))
+	}
 
-		EraInfo::<T>::set_sequencer(new_planned_era, bounded_sequencers.clone());
+	/// Split the elected `sequencers` into groups of `SequencersPerGroup`.
+	///
+	/// At most `GroupCount` complete groups are formed out of the best ranked sequencers. They
+	/// are shuffled with `seed` and then assigned to the groups by [`Config::GroupAssignment`].
+	/// The sequencers left over, either because they do not fill a complete group or because all
+	/// the groups are already formed, are returned as standby.
+	pub(crate) fn form_groups(
+		sequencers: &BoundedVec<Sequencer<T>, T::MaxSequencers>,
+		seed: &T::Hash,
+	) -> (Vec<SequencerGroup<T>>, BoundedVec<T::AccountId, T::MaxSequencers>) {
+		let group_size = Self::sequencers_per_group() as usize;
+		let group_count = if group_size == 0 {
+			0
+		} else {
+			(Self::group_count() as usize).min(sequencers.len() / group_size)
+		};
+		let (grouped, standby) = sequencers.split_at(group_count * group_size);
+
+		let mut grouped = grouped
+			.iter()
+			.map(|sequencer| (sequencer.who.clone(), sequencer.stake))
+			.collect::<Vec<_>>();
+		shuffle(&mut grouped, seed);
 
-		log::info!(
-			"New era #{} has started at session {}",
-			new_planned_era,
-			start_session_index,
+		let groups = T::GroupAssignment::assign(grouped, group_count, group_size)
+			.into_iter()
+			.map(|group| SequencerGroup {
+				total_stake: group.iter().fold(Zero::zero(), |total: BalanceOf<T>, (_, stake)| {
+					total.saturating_add(*stake)
+				}),
+				// Out of `sequencers`, the members and the standby never exceed the bound.
+				members: BoundedVec::truncate_from(group.into_iter().map(|(who, _)| who).collect()),
+			})
+			.collect();
+		let standby = BoundedVec::truncate_from(
+			standby.iter().map(|sequencer| sequencer.who.clone()).collect(),
 		);
 
-		Some(bounded_sequencers)
+		(groups, standby)
 	}
 
 	fn new_session(
 		session_index: SessionIndex,
 		validators: &Vec<T::AccountId>,
-	) -> Option<BoundedVec<Sequencer<T>, ConstU32<{ u32::MAX }>>> {
+	) -> Option<BoundedVec<Sequencer<T>, T::MaxSequencers>> {
 		if let Some(current_era) = Self::current_era() {
 			let current_era_start_session_index = Self::eras_start_session_index(current_era)
 				.unwrap_or_else(|| {
@@ -281,7 +1305,7 @@ impl<T: Config> Pallet<T> {
 				// Short circuit to `try_trigger_new_era`.
 				Forcing::ForceAlways => (),
 				// Only go to `try_trigger_new_era` if deadline reached.
-				Forcing::NotForcing if era_length >= T::SessionsPerEra::get() => (),
+				Forcing::NotForcing if era_length >= Self::current_config().sessions_per_era => (),
 				_ => return None,
 			}
 
@@ -289,12 +1313,83 @@ impl<T: Config> Pallet<T> {
 			let maybe_new_era_validators = Self::try_trigger_new_era(session_index, validators);
 			maybe_new_era_validators
 		} else {
+			if Self::force_era() == Forcing::ForceNone {
+				log::info!("Not starting the first era, eras are forced off.");
+				return None;
+			}
+
 			// Set initial era.
 			log::info!("Starting the first era.");
 			Self::try_trigger_new_era(session_index, validators)
 		}
 	}
 
+	/// The validators of session `session_index` in [`SessionMode::Authority`].
+	///
+	/// The candidates are the best staked registered operators which registered their session
+	/// keys, see [`Pallet::top_candidates`]. When a new era is planned, its validators are
+	/// returned, see [`Pallet::planned_validators`]. Otherwise, the validators do not change.
+	fn sequencer_validators(session_index: SessionIndex) -> Option<Vec<T::AccountId>> {
+		let candidates = Self::top_candidates();
+		Self::new_session(session_index, &candidates)?;
+		Some(Self::planned_validators())
+	}
+
+	/// The elected sequencers of the current era which can validate, or the invulnerables if
+	/// there are none.
+	fn planned_validators() -> Vec<T::AccountId> {
+		let validators = Self::current_era()
+			.map(|era| {
+				Self::eras_sequencers(era)
+					.into_iter()
+					.map(|sequencer| sequencer.who)
+					.filter(|who| T::ValidatorRegistration::is_registered(who))
+					.collect::<Vec<_>>()
+			})
+			.unwrap_or_default();
+		if validators.is_empty() {
+			log::warn!("No elected sequencer can validate, falling back to the invulnerables");
+			T::Invulnerables::get()
+		} else {
+			validators
+		}
+	}
+
+	/// Plan era 0 with the given `sequencers` instead of electing them.
+	///
+	/// They are ranked by their own stake, the era starts at session 0.
+	fn plan_genesis_era(sequencers: &[T::AccountId]) {
+		assert!(!sequencers.is_empty(), "the genesis sequencers must not be empty");
+
+		let mut exposures = sequencers
+			.iter()
+			.map(|who| {
+				assert!(
+					Registrations::<T>::contains_key(who),
+					"genesis sequencer {:?} is not a genesis staker",
+					who,
+				);
+				let own = Self::restake_data(who);
+				(who.clone(), Exposure { total: own, own, others: Vec::new() })
+			})
+			.collect::<Vec<_>>();
+		exposures.sort_by(|a, b| by_rank(&(&a.0, a.1.total), &(&b.0, b.1.total)));
+		exposures.dedup_by(|a, b| a.0 == b.0);
+
+		let sequencers = exposures
+			.iter()
+			.enumerate()
+			.map(|(rank, (who, exposure))| ElectedSequencer {
+				who: who.clone(),
+				stake: exposure.total,
+				rank: rank as u32,
+			})
+			.collect::<Vec<_>>();
+		let sequencers = BoundedVec::try_from(sequencers).expect("too many genesis sequencers");
+		Self::trigger_new_era(0, sequencers, exposures)
+			.expect("the genesis sequencers can not be grouped");
+	}
+
 	/// Start a session potentially starting an era.
 	fn start_session(start_session: SessionIndex) {
 		let next_active_era = Self::active_era().map(|e| e.index + 1).unwrap_or(0);
@@ -314,17 +1409,26 @@ impl<T: Config> Pallet<T> {
 	}
 
 	/// Start a new era. It does:
+	/// * End the active era, computing its reward,
 	/// * Increment `active_era.index`,
 	/// * reset `active_era.start`,
+	/// * apply the slashes deferred to the new era.
 	fn start_era() {
-		ActiveEra::<T>::mutate(|active_era| {
+		if let Some(active_era) = Self::active_era() {
+			Self::end_era(active_era);
+		}
+
+		let new_index = ActiveEra::<T>::mutate(|active_era| {
 			let new_index = active_era.as_ref().map(|info| info.index + 1).unwrap_or(0);
 			*active_era = Some(ActiveEraInfo {
 				index: new_index,
 				// Set new active era start in next `on_finalize`. To guarantee usage of `Time`
 				start: None,
 			});
+			new_index
 		});
+
+		Self::apply_unapplied_slashes(new_index);
 	}
 }
 
@@ -335,14 +1439,50 @@ pub struct EraInfo<T>(sp_std::marker::PhantomData<T>);
 
 impl<T: Config> EraInfo<T> {
 	/// Store exposure for elected sequencers at start of an era.
-	pub fn set_sequencer(
-		era: EraIndex,
-		sequencers: BoundedVec<(T::AccountId, u128), ConstU32<{ u32::MAX }>>,
-	) {
+	pub fn set_sequencer(era: EraIndex, sequencers: BoundedVec<Sequencer<T>, T::MaxSequencers>) {
+		let total_stake = sequencers
+			.iter()
+			.fold(Zero::zero(), |total: BalanceOf<T>, s| total.saturating_add(s.stake));
+
 		<ErasSequencers<T>>::insert(era, &sequencers);
+		<ErasTotalStake<T>>::insert(era, total_stake);
+	}
+
+	/// Store the stake backing the elected sequencers and snapshot their commission.
+	pub fn set_exposures(era: EraIndex, exposures: Vec<(T::AccountId, SequencerExposure<T>)>) {
+		for (who, exposure) in exposures {
+			<ErasCommissions<T>>::insert(era, &who, Pallet::<T>::commissions(&who));
+			<ErasExposures<T>>::insert(era, who, exposure);
+		}
+	}
+
+	/// Store the sequencer groups and the standby sequencers of an era, along with the seed used
+	/// to shuffle them.
+	///
+	/// Every group holds at least one of the elected sequencers, so there can not be more groups
+	/// than [`Config::MaxSequencers`]. Nothing is stored if there are.
+	pub fn set_groups(
+		era: EraIndex,
+		groups: Vec<SequencerGroup<T>>,
+		standby: BoundedVec<T::AccountId, T::MaxSequencers>,
+		seed: T::Hash,
+	) -> DispatchResult {
+		let groups: BoundedVec<SequencerGroup<T>, T::MaxSequencers> =
+			groups.try_into().map_err(|_| Error::<T>::TooManyGroups)?;
+
+		<ErasSequencerGroups<T>>::insert(era, &groups);
+		<ErasStandbySequencers<T>>::insert(era, standby);
+		<ErasGroupSeed<T>>::insert(era, seed);
+		Ok(())
 	}
 }
 
+/// Session manager wrapping `I`, electing the sequencers at the era boundaries.
+///
+/// In [`SessionMode::Tag`], the sequencers are elected out of the validators of `I`, which stay
+/// the validators of the session. In [`SessionMode::Authority`], `I` is still called but its
+/// validators are ignored: the sequencers are elected out of the registered operators and
+/// become the validators of the session, see [`Pallet::sequencer_validators`].
 pub struct SessionManager<I, T>(sp_std::marker::PhantomData<(I, T)>);
 
 impl<I, T> pallet_session::SessionManager<<T as frame_system::Config>::AccountId>
@@ -353,20 +1493,32 @@ where
 {
 	fn new_session(new_index: SessionIndex) -> Option<Vec<<T as frame_system::Config>::AccountId>> {
 		let new_session = I::new_session(new_index);
-		if let Some(validators) = &new_session {
-			Pallet::<T>::new_session(new_index, validators);
+		match T::SessionMode::get() {
+			SessionMode::Tag => {
+				if let Some(validators) = &new_session {
+					Pallet::<T>::new_session(new_index, validators);
+				}
+				new_session
+			},
+			SessionMode::Authority => Pallet::<T>::sequencer_validators(new_index),
 		}
-		new_session
 	}
 
 	fn new_session_genesis(
 		new_index: SessionIndex,
 	) -> Option<Vec<<T as frame_system::Config>::AccountId>> {
 		let new_session = I::new_session_genesis(new_index);
-		if let Some(validators) = &new_session {
-			Pallet::<T>::new_session(new_index, validators);
+		match T::SessionMode::get() {
+			SessionMode::Tag => {
+				if let Some(validators) = &new_session {
+					Pallet::<T>::new_session(new_index, validators);
+				}
+				new_session
+			},
+			// The genesis sessions need validators, even if the first election fails.
+			SessionMode::Authority => Pallet::<T>::sequencer_validators(new_index)
+				.or_else(|| Some(Pallet::<T>::planned_validators())),
 		}
-		new_session
 	}
 
 	fn end_session(end_index: SessionIndex) {
@@ -0,0 +1,157 @@
+//! Storage migrations of the sequencer pallet.
+//!
+//! Every migration only runs on the storage version it migrates from, and bumps it.
+
+use super::*;
+use frame_support::{
+	pallet_prelude::Weight,
+	traits::{GetStorageVersion, OnRuntimeUpgrade, StorageVersion},
+};
+#[cfg(feature = "try-runtime")]
+use sp_runtime::TryRuntimeError;
+
+/// Remove the restake data set by the former `deposit` call.
+pub mod v1 {
+	use super::*;
+
+	/// The bonds are held since version 1. The former `deposit` call let anybody set any amount
+	/// for any account without holding anything, so the amounts it set can not be trusted and are
+	/// removed rather than held.
+	pub struct MigrateToV1<T>(sp_std::marker::PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV1<T> {
+		fn on_runtime_upgrade() -> Weight {
+			let db_weight = T::DbWeight::get();
+			if Pallet::<T>::on_chain_storage_version() != 0 {
+				log::info!("Sequencer migration to v1 skipped, the storage is not at v0");
+				return db_weight.reads(1);
+			}
+
+			// Only the accounts the former `deposit` call was called for are in the map, removing
+			// them all at once is weighed from what was actually read and removed.
+			let result = RestakeData::<T>::clear(u32::MAX, None);
+			StorageVersion::new(1).put::<Pallet<T>>();
+			log::info!(
+				"Sequencer migration to v1 removed {} unheld restake entries",
+				result.unique
+			);
+
+			db_weight.reads_writes(u64::from(result.loops) + 1, u64::from(result.unique) + 1)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(_state: Vec<u8>) -> Result<(), TryRuntimeError> {
+			ensure!(Pallet::<T>::on_chain_storage_version() >= 1, "storage version not bumped");
+			Ok(())
+		}
+	}
+}
+
+/// Rank the sequencers of the eras elected before the sequencers held their rank.
+pub mod v2 {
+	use super::*;
+	use sp_runtime::traits::SaturatedConversion;
+
+	/// The sequencers of an era up to version 1, with their stake.
+	type OldSequencers<T> = Vec<(<T as frame_system::Config>::AccountId, u128)>;
+
+	/// The sequencers of an era are [`ElectedSequencer`]s, bounded by [`Config::MaxSequencers`],
+	/// since version 2. They are ranked by stake, the ones past the bound are dropped, and the
+	/// total stake of the era is recorded.
+	pub struct MigrateToV2<T>(sp_std::marker::PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV2<T> {
+		fn on_runtime_upgrade() -> Weight {
+			let db_weight = T::DbWeight::get();
+			if Pallet::<T>::on_chain_storage_version() != 1 {
+				log::info!("Sequencer migration to v2 skipped, the storage is not at v1");
+				return db_weight.reads(1);
+			}
+
+			let mut translated = 0u64;
+			ErasSequencers::<T>::translate::<OldSequencers<T>, _>(|era, mut old| {
+				translated.saturating_inc();
+				old.sort_by(|a, b| by_rank(&(&a.0, a.1), &(&b.0, b.1)));
+				let sequencers = old
+					.into_iter()
+					.enumerate()
+					.map(|(rank, (who, stake))| ElectedSequencer {
+						who,
+						stake: stake.saturated_into::<BalanceOf<T>>(),
+						rank: rank as u32,
+					})
+					.collect::<Vec<_>>();
+				let sequencers = BoundedVec::truncate_from(sequencers);
+				let total_stake = sequencers
+					.iter()
+					.fold(Zero::zero(), |total: BalanceOf<T>, s| total.saturating_add(s.stake));
+				ErasTotalStake::<T>::insert(era, total_stake);
+				Some(sequencers)
+			});
+			StorageVersion::new(2).put::<Pallet<T>>();
+			log::info!("Sequencer migration to v2 ranked the sequencers of {} eras", translated);
+
+			db_weight.reads_writes(translated + 1, 2 * translated + 1)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(_state: Vec<u8>) -> Result<(), TryRuntimeError> {
+			ensure!(Pallet::<T>::on_chain_storage_version() >= 2, "storage version not bumped");
+			for (_, sequencers) in ErasSequencers::<T>::iter() {
+				ensure!(
+					sequencers.iter().enumerate().all(|(rank, s)| s.rank == rank as u32),
+					"sequencers not ranked"
+				);
+			}
+			Ok(())
+		}
+	}
+}
+
+/// Compute the aggregates of the stakers and sort them.
+pub mod v3 {
+	use super::*;
+
+	/// [`TotalRestake`], [`RestakerCount`] and [`Config::StakerList`] are kept up to date with
+	/// every bond since version 3. They are computed once over [`RestakeData`].
+	pub struct MigrateToV3<T>(sp_std::marker::PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV3<T> {
+		fn on_runtime_upgrade() -> Weight {
+			let db_weight = T::DbWeight::get();
+			if Pallet::<T>::on_chain_storage_version() != 2 {
+				log::info!("Sequencer migration to v3 skipped, the storage is not at v2");
+				return db_weight.reads(1);
+			}
+
+			let mut total: BalanceOf<T> = Zero::zero();
+			let mut count = 0u32;
+			for (who, bonded) in RestakeData::<T>::iter() {
+				total = total.saturating_add(bonded);
+				count.saturating_inc();
+				Pallet::<T>::update_staker_list(&who);
+			}
+			TotalRestake::<T>::put(total);
+			RestakerCount::<T>::put(count);
+			StorageVersion::new(3).put::<Pallet<T>>();
+			log::info!("Sequencer migration to v3 sorted {} stakers", count);
+
+			// Reading the bond and the delegations, and inserting into the list.
+			let count = u64::from(count);
+			db_weight.reads_writes(4 * count + 1, 2 * count + 3)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(_state: Vec<u8>) -> Result<(), TryRuntimeError> {
+			ensure!(Pallet::<T>::on_chain_storage_version() >= 3, "storage version not bumped");
+			let (total, count) = RestakeData::<T>::iter()
+				.fold((BalanceOf::<T>::zero(), 0u32), |(total, count), (_, bonded)| {
+					(total.saturating_add(bonded), count + 1)
+				});
+			ensure!(TotalRestake::<T>::get() == total, "total restake out of sync");
+			ensure!(RestakerCount::<T>::get() == count, "restaker count out of sync");
+			ensure!(T::StakerList::count() == count, "staker list out of sync");
+			Ok(())
+		}
+	}
+}
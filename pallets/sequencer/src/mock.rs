@@ -1,20 +1,48 @@
-use frame_support::{parameter_types, traits::Everything};
+use crate::{self as pallet_sequencer, EraConfig, Forcing, GroupAssignment, SequencerInfo};
+use frame_support::{
+	parameter_types,
+	traits::{
+		ConstU32, ConstU64, Everything, GetDefault, Hooks, Randomness, ValidatorRegistration,
+	},
+	weights::{constants::RocksDbWeight, Weight},
+};
 use frame_system as system;
 use sp_core::H256;
 use sp_runtime::{
-	traits::{BlakeTwo256, IdentityLookup},
-	BuildStorage,
+	key_types,
+	testing::UintAuthorityId,
+	traits::{BlakeTwo256, ConvertInto, Hash, IdentityLookup, OpaqueKeys},
+	BuildStorage, KeyTypeId, Perbill,
 };
 use sp_staking::SessionIndex;
 
 type Block = frame_system::mocking::MockBlock<Test>;
 
+/// Number of blocks in a session.
+pub const BLOCKS_PER_SESSION: u64 = 3;
+/// Number of milliseconds between two blocks.
+pub const BLOCK_TIME: u64 = 1_000;
+/// The timestamp of the first block.
+pub const INIT_TIMESTAMP: u64 = 30_000;
+/// The balance of the accounts endowed at genesis.
+pub const ENDOWMENT: u128 = 1_000_000;
+/// The account paying the era rewards.
+pub const REWARD_POT: u64 = 99;
+/// The reward of every era.
+pub const ERA_REWARD: u128 = 1_000;
+
 // Configure a mock runtime to test the pallet.
 frame_support::construct_runtime!(
 	pub enum Test
 	{
 		System: frame_system::{Pallet, Call, Config<T>, Storage, Event<T>},
-		SequencerModule: crate::{Pallet, Call, Storage, Event<T>},
+		Timestamp: pallet_timestamp::{Pallet, Call, Storage, Inherent},
+		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+		StakerList: pallet_bags_list::{Pallet, Call, Storage, Event<T>},
+		// Before the session pallet, so the genesis stakers are in when the first sessions are
+		// planned.
+		SequencerModule: pallet_sequencer::{Pallet, Call, Storage, Config<T>, Event<T>, HoldReason},
+		Session: pallet_session::{Pallet, Call, Storage, Config<T>, Event},
 	}
 );
 
@@ -27,7 +55,7 @@ impl system::Config for Test {
 	type BaseCallFilter = Everything;
 	type BlockWeights = ();
 	type BlockLength = ();
-	type DbWeight = ();
+	type DbWeight = RocksDbWeight;
 	type RuntimeOrigin = RuntimeOrigin;
 	type RuntimeCall = RuntimeCall;
 	type Nonce = u64;
@@ -40,25 +68,347 @@ impl system::Config for Test {
 	type BlockHashCount = BlockHashCount;
 	type Version = ();
 	type PalletInfo = PalletInfo;
-	type AccountData = ();
+	type AccountData = pallet_balances::AccountData<u128>;
 	type OnNewAccount = ();
 	type OnKilledAccount = ();
 	type SystemWeightInfo = ();
 	type SS58Prefix = SS58Prefix;
 	type OnSetCode = ();
-	type MaxConsumers = frame_support::traits::ConstU32<16>;
+	type MaxConsumers = ConstU32<16>;
+}
+
+impl pallet_timestamp::Config for Test {
+	type Moment = u64;
+	type OnTimestampSet = ();
+	type MinimumPeriod = ConstU64<5>;
+	type WeightInfo = ();
+}
+
+impl pallet_balances::Config for Test {
+	type MaxLocks = ();
+	type MaxReserves = ();
+	type ReserveIdentifier = [u8; 8];
+	type Balance = u128;
+	type RuntimeEvent = RuntimeEvent;
+	type DustRemoval = ();
+	type ExistentialDeposit = frame_support::traits::ConstU128<1>;
+	type AccountStore = System;
+	type WeightInfo = ();
+	type FreezeIdentifier = ();
+	type MaxFreezes = ();
+	type RuntimeHoldReason = RuntimeHoldReason;
+	type RuntimeFreezeReason = RuntimeFreezeReason;
+	type MaxHolds = ConstU32<2>;
 }
 
 parameter_types! {
-	pub const SessionsPerEra: SessionIndex = 1;
+	pub const SessionsPerEra: SessionIndex = 3;
+	pub const PointsRewardPortion: Perbill = Perbill::from_percent(50);
+	pub const RewardPotAccount: u64 = REWARD_POT;
+	pub const EraReward: u128 = ERA_REWARD;
+	pub static SequencerSessionMode: crate::SessionMode = crate::SessionMode::Tag;
+	/// The validators handed out by [`TestSessionManager`].
+	pub static Validators: Vec<u64> = vec![11, 21, 31, 41];
+	/// The number of empty groups [`TestGroupAssignment`] adds to the groups it forms.
+	pub static ExtraGroups: usize = 0;
+}
+
+/// Deterministic randomness, only depending on the subject.
+pub struct TestRandomness;
+
+impl Randomness<H256, u64> for TestRandomness {
+	fn random(subject: &[u8]) -> (H256, u64) {
+		(BlakeTwo256::hash(subject), System::block_number())
+	}
+}
+
+/// [`crate::GreedyBalance`], forming [`ExtraGroups`] more empty groups than asked for.
+pub struct TestGroupAssignment;
+
+impl GroupAssignment<u64, u128> for TestGroupAssignment {
+	fn assign(
+		sequencers: Vec<(u64, u128)>,
+		group_count: usize,
+		group_size: usize,
+	) -> Vec<Vec<(u64, u128)>> {
+		let mut groups = crate::GreedyBalance::assign(sequencers, group_count, group_size);
+		groups.resize(groups.len() + ExtraGroups::get(), Vec::new());
+		groups
+	}
+}
+
+/// Every account has registered its session keys.
+pub struct AllRegistered;
+
+impl ValidatorRegistration<u64> for AllRegistered {
+	fn is_registered(_id: &u64) -> bool {
+		true
+	}
+}
+
+/// Session manager standing for the collator selection, the [`Validators`] validate every
+/// session.
+pub struct TestSessionManager;
+
+impl pallet_session::SessionManager<u64> for TestSessionManager {
+	fn new_session(_new_index: SessionIndex) -> Option<Vec<u64>> {
+		Some(Validators::get())
+	}
+
+	fn end_session(_end_index: SessionIndex) {}
+
+	fn start_session(_start_index: SessionIndex) {}
+}
+
+/// Session handler ignoring the keys.
+pub struct TestSessionHandler;
+
+impl pallet_session::SessionHandler<u64> for TestSessionHandler {
+	const KEY_TYPE_IDS: &'static [KeyTypeId] = &[key_types::DUMMY];
+
+	fn on_genesis_session<Ks: OpaqueKeys>(_validators: &[(u64, Ks)]) {}
+
+	fn on_new_session<Ks: OpaqueKeys>(
+		_changed: bool,
+		_validators: &[(u64, Ks)],
+		_queued_validators: &[(u64, Ks)],
+	) {
+	}
+
+	fn on_disabled(_validator_index: u32) {}
+}
+
+impl pallet_session::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type ValidatorId = u64;
+	type ValidatorIdOf = ConvertInto;
+	type ShouldEndSession =
+		pallet_session::PeriodicSessions<ConstU64<BLOCKS_PER_SESSION>, ConstU64<0>>;
+	type NextSessionRotation =
+		pallet_session::PeriodicSessions<ConstU64<BLOCKS_PER_SESSION>, ConstU64<0>>;
+	type SessionManager = pallet_sequencer::SessionManager<TestSessionManager, Test>;
+	type SessionHandler = TestSessionHandler;
+	type Keys = UintAuthorityId;
+	type WeightInfo = ();
+}
+
+parameter_types! {
+	pub static BagThresholds: &'static [u128] = &[10, 20, 30, 40, 50, 60, 1_000, 2_000, 10_000];
+	pub static MaxCandidates: u32 = 64;
+}
+
+impl pallet_bags_list::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type WeightInfo = ();
+	type BagThresholds = BagThresholds;
+	type ScoreProvider = SequencerModule;
+	type Score = u128;
 }
 
 impl crate::Config for Test {
 	type RuntimeEvent = RuntimeEvent;
+	type Currency = Balances;
+	type RuntimeHoldReason = RuntimeHoldReason;
 	type SessionsPerEra = SessionsPerEra;
+	type HistoryDepth = ConstU32<4>;
+	type MinSequencerCount = ConstU32<1>;
+	type MaxSequencers = ConstU32<16>;
+	type MaxCandidates = MaxCandidates;
+	type StakerList = StakerList;
+	type BondingDuration = ConstU32<3>;
+	type MaxUnlockingChunks = ConstU32<4>;
+	type Election = crate::TopN;
+	type GroupAssignment = TestGroupAssignment;
+	type Randomness = TestRandomness;
+	type AdminOrigin = frame_system::EnsureRoot<u64>;
+	type SlashDeferDuration = ConstU32<2>;
+	type MaxFieldLength = ConstU32<64>;
+	type MaxEndpoints = ConstU32<4>;
+	type RegistrationDepositBase = frame_support::traits::ConstU128<10>;
+	type RegistrationDepositPerByte = frame_support::traits::ConstU128<1>;
+	type MaxDelegators = ConstU32<8>;
+	type MaxUnappliedSlashes = ConstU32<16>;
+	type Slash = ();
+	type RewardSource = crate::RewardPot<Balances, RewardPotAccount, EraReward>;
+	type PointsRewardPortion = PointsRewardPortion;
+	type UnixTime = Timestamp;
+	type SessionMode = SequencerSessionMode;
+	type ValidatorRegistration = AllRegistered;
+	type Invulnerables = GetDefault;
+	type WeightInfo = ();
+}
+
+/// Builder of the genesis of the tests.
+pub struct ExtBuilder {
+	stakers: Vec<(u64, u128)>,
+	validators: Vec<u64>,
+	era_config: Option<EraConfig>,
+	sequencers_per_group: u32,
+	group_count: u32,
+	force_era: Forcing,
+	session_mode: crate::SessionMode,
+	sequencers: Option<Vec<u64>>,
+}
+
+impl Default for ExtBuilder {
+	fn default() -> Self {
+		Self {
+			stakers: vec![(11, 1_000), (21, 2_000), (31, 3_000), (41, 4_000)],
+			validators: vec![11, 21, 31, 41],
+			era_config: None,
+			sequencers_per_group: 2,
+			group_count: 2,
+			force_era: Forcing::NotForcing,
+			session_mode: crate::SessionMode::Tag,
+			sequencers: None,
+		}
+	}
+}
+
+impl ExtBuilder {
+	/// The operators bonding and registering at genesis.
+	pub fn stakers(mut self, stakers: Vec<(u64, u128)>) -> Self {
+		self.stakers = stakers;
+		self
+	}
+
+	/// The validators of every session, the candidates of the elections in
+	/// [`crate::SessionMode::Tag`].
+	pub fn validators(mut self, validators: Vec<u64>) -> Self {
+		self.validators = validators;
+		self
+	}
+
+	pub fn era_config(mut self, config: EraConfig) -> Self {
+		self.era_config = Some(config);
+		self
+	}
+
+	pub fn groups(mut self, sequencers_per_group: u32, group_count: u32) -> Self {
+		self.sequencers_per_group = sequencers_per_group;
+		self.group_count = group_count;
+		self
+	}
+
+	pub fn force_era(mut self, force_era: Forcing) -> Self {
+		self.force_era = force_era;
+		self
+	}
+
+	pub fn session_mode(mut self, session_mode: crate::SessionMode) -> Self {
+		self.session_mode = session_mode;
+		self
+	}
+
+	/// The sequencers of era 0, instead of electing them out of the first validators.
+	pub fn sequencers(mut self, sequencers: Vec<u64>) -> Self {
+		self.sequencers = Some(sequencers);
+		self
+	}
+
+	pub fn build(self) -> sp_io::TestExternalities {
+		Validators::set(self.validators.clone());
+		SequencerSessionMode::set(self.session_mode);
+
+		let mut accounts = vec![1, 2, 3, 4, 5, 51, 61, REWARD_POT];
+		accounts.extend(self.stakers.iter().map(|(who, _)| *who));
+		accounts.extend(self.validators.iter().copied());
+		accounts.sort();
+		accounts.dedup();
+
+		let mut storage = system::GenesisConfig::<Test>::default().build_storage().unwrap();
+		pallet_balances::GenesisConfig::<Test> {
+			balances: accounts.iter().map(|who| (*who, ENDOWMENT)).collect(),
+		}
+		.assimilate_storage(&mut storage)
+		.unwrap();
+		pallet_sequencer::GenesisConfig::<Test> {
+			era_config: self.era_config,
+			stakers: self.stakers,
+			sequencers_per_group: self.sequencers_per_group,
+			group_count: self.group_count,
+			force_era: self.force_era,
+			sequencers: self.sequencers,
+		}
+		.assimilate_storage(&mut storage)
+		.unwrap();
+		pallet_session::GenesisConfig::<Test> {
+			keys: accounts.iter().map(|who| (*who, *who, UintAuthorityId(*who))).collect(),
+		}
+		.assimilate_storage(&mut storage)
+		.unwrap();
+
+		let mut ext = sp_io::TestExternalities::from(storage);
+		ext.execute_with(|| {
+			System::set_block_number(1);
+			Timestamp::set_timestamp(INIT_TIMESTAMP);
+		});
+		ext
+	}
 }
 
 // Build genesis storage according to the mock runtime.
 pub fn new_test_ext() -> sp_io::TestExternalities {
-	system::GenesisConfig::<Test>::default().build_storage().unwrap().into()
+	ExtBuilder::default().build()
+}
+
+/// Run the blocks up to `n`, the idle weight of every block going to the pruning.
+pub fn run_to_block(n: u64) {
+	while System::block_number() < n {
+		let block = System::block_number();
+		SequencerModule::on_idle(block, Weight::MAX);
+		SequencerModule::on_finalize(block);
+		System::on_finalize(block);
+
+		let block = block + 1;
+		System::set_block_number(block);
+		Timestamp::set_timestamp(INIT_TIMESTAMP + (block - 1) * BLOCK_TIME);
+		Session::on_initialize(block);
+	}
+}
+
+/// Run the blocks up to the start of the next session.
+pub fn advance_session() {
+	let next_session = u64::from(Session::current_index()) + 1;
+	run_to_block(next_session * BLOCKS_PER_SESSION);
+}
+
+/// Run the sessions up to the start of the next era.
+pub fn advance_era() {
+	let next_era = active_era().map_or(0, |era| era + 1);
+	for _ in 0..100 {
+		if active_era() == Some(next_era) {
+			return;
+		}
+		advance_session();
+	}
+	panic!("era {} did not start within 100 sessions", next_era);
+}
+
+/// The index of the active era.
+pub fn active_era() -> Option<u32> {
+	SequencerModule::active_era().map(|info| info.index)
+}
+
+/// The events of the sequencer pallet, since the last call.
+pub fn sequencer_events() -> Vec<pallet_sequencer::Event<Test>> {
+	let events = System::events()
+		.into_iter()
+		.filter_map(|record| match record.event {
+			RuntimeEvent::SequencerModule(event) => Some(event),
+			_ => None,
+		})
+		.collect();
+	System::reset_events();
+	events
+}
+
+/// The info of an operator, every field empty.
+pub fn empty_info() -> SequencerInfo<Test> {
+	SequencerInfo {
+		display_name: Default::default(),
+		rpc_endpoints: Default::default(),
+		p2p_endpoints: Default::default(),
+		contact: Default::default(),
+	}
 }
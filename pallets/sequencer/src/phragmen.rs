@@ -0,0 +1,128 @@
+//! Sequential Phragmén election of the sequencers.
+
+use crate::election::{ElectionError, SequencerElection, StakeGraph};
+use ep_sequencer::{Exposure, IndividualExposure};
+use frame_support::{
+	traits::{fungible::Inspect, Get},
+	BoundedVec,
+};
+use sp_npos_elections::{
+	assignment_ratio_to_staked_normalized, seq_phragmen, to_supports, BalancingConfig,
+	ElectionResult, IdentifierT, VoteWeight,
+};
+use sp_runtime::{traits::Saturating, Perbill};
+use sp_staking::currency_to_vote::CurrencyToVote;
+use sp_std::{
+	collections::{btree_map::BTreeMap, btree_set::BTreeSet},
+	vec::Vec,
+};
+
+/// Elect the sequencers with sequential Phragmén over the delegation graph.
+///
+/// Every operator backs itself with its own stake and every delegator backs all the operators
+/// it delegates to, with the sum of its delegations to them. Phragmén elects `seats` of the
+/// operators, at least `min_count`, maximising the backing of the least backed one, and splits
+/// the stake of every voter between the elected operators it backs. If `Balancing` returns a
+/// config, the split is then balanced to even out the backing of the elected operators.
+///
+/// A delegator piling onto a single big operator thus does not get it more than its share, the
+/// stake of the delegators backing several operators goes where it is needed the most.
+///
+/// Stakes are turned into votes by `C2V`, against the total issuance of `Currency`.
+pub struct SequentialPhragmen<Currency, C2V, Balancing>(
+	sp_std::marker::PhantomData<(Currency, C2V, Balancing)>,
+);
+
+impl<AccountId, Currency, C2V, Balancing> SequencerElection<AccountId, Currency::Balance>
+	for SequentialPhragmen<Currency, C2V, Balancing>
+where
+	AccountId: IdentifierT,
+	Currency: Inspect<AccountId>,
+	C2V: CurrencyToVote<Currency::Balance>,
+	Balancing: Get<Option<BalancingConfig>>,
+{
+	fn elect<Bound: Get<u32>>(
+		candidates: Vec<(AccountId, Currency::Balance)>,
+		seats: u32,
+		min_count: u32,
+	) -> Result<BoundedVec<(AccountId, Currency::Balance), Bound>, ElectionError> {
+		// Without the delegation graph, every candidate is only backed by itself.
+		let graph = StakeGraph { candidates, delegations: Vec::new() };
+		Self::elect_exposures::<Bound>(graph, seats, min_count)?
+			.into_iter()
+			.map(|(who, exposure)| (who, exposure.total))
+			.collect::<Vec<_>>()
+			.try_into()
+			.map_err(|_| ElectionError::TooManyWinners)
+	}
+
+	fn elect_exposures<Bound: Get<u32>>(
+		graph: StakeGraph<AccountId, Currency::Balance>,
+		seats: u32,
+		min_count: u32,
+	) -> Result<BoundedVec<(AccountId, Exposure<AccountId, Currency::Balance>), Bound>, ElectionError>
+	{
+		if graph.candidates.is_empty() {
+			return Err(ElectionError::NoCandidates);
+		}
+
+		// The stake of every voter and the operators it backs.
+		let mut voters = BTreeMap::<AccountId, (Currency::Balance, Vec<AccountId>)>::new();
+		for (operator, own) in &graph.candidates {
+			let (stake, targets) = voters.entry(operator.clone()).or_default();
+			*stake = stake.saturating_add(*own);
+			targets.push(operator.clone());
+		}
+		let targets = graph.candidates.into_iter().map(|(who, _)| who).collect::<Vec<_>>();
+		let is_target = targets.iter().collect::<BTreeSet<_>>();
+		for (delegator, operator, value) in graph.delegations {
+			if !is_target.contains(&operator) {
+				continue;
+			}
+			let (stake, backed) = voters.entry(delegator).or_default();
+			*stake = stake.saturating_add(value);
+			if !backed.contains(&operator) {
+				backed.push(operator);
+			}
+		}
+
+		let issuance = Currency::total_issuance();
+		let to_vote = |stake: Currency::Balance| -> VoteWeight { C2V::to_vote(stake, issuance) };
+		let votes = voters
+			.iter()
+			.map(|(who, (stake, backed))| (who.clone(), to_vote(*stake), backed.clone()))
+			.collect::<Vec<_>>();
+
+		let to_elect = (seats.max(min_count) as usize).min(targets.len());
+		let ElectionResult { winners, assignments } =
+			seq_phragmen::<AccountId, Perbill>(to_elect, targets, votes, Balancing::get())
+				.map_err(|_| ElectionError::Algorithm)?;
+
+		let staked = assignment_ratio_to_staked_normalized(assignments, |who| {
+			voters.get(who).map_or(0, |(stake, _)| to_vote(*stake))
+		})
+		.map_err(|_| ElectionError::Algorithm)?;
+		let mut supports = to_supports(&staked).into_iter().collect::<BTreeMap<_, _>>();
+
+		winners
+			.into_iter()
+			.map(|(who, _)| {
+				let backers =
+					supports.remove(&who).map(|support| support.voters).unwrap_or_default();
+				let mut exposure = Exposure::default();
+				for (voter, backing) in backers {
+					let value = C2V::to_currency(backing, issuance);
+					exposure.total = exposure.total.saturating_add(value);
+					if voter == who {
+						exposure.own = exposure.own.saturating_add(value);
+					} else {
+						exposure.others.push(IndividualExposure { who: voter, value });
+					}
+				}
+				(who, exposure)
+			})
+			.collect::<Vec<_>>()
+			.try_into()
+			.map_err(|_| ElectionError::TooManyWinners)
+	}
+}
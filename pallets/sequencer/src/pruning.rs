@@ -0,0 +1,207 @@
+//! Pruning of the eras which fall out of history.
+//!
+//! Once an era is more than `history_depth` eras older than the active era, everything
+//! recorded for it is removed. The pruning is done lazily in `on_idle`, a few entries at a time
+//! so it never takes more than the weight left in the block. [`HistoryPruning`] records where
+//! the pruning is at, to resume it in a later block.
+
+use super::*;
+use frame_support::{defensive, pallet_prelude::Weight, DefaultNoBound};
+use sp_io::MultiRemovalResults;
+use sp_runtime::{traits::SaturatedConversion, RuntimeDebug};
+use sp_std::marker::PhantomData;
+
+/// The storage items of an era being pruned, in the order they are pruned in.
+#[derive(
+	Copy, Clone, Default, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo, MaxEncodedLen,
+)]
+pub enum PruningStage {
+	/// The maps holding a single value per era.
+	#[default]
+	Values,
+	/// [`ClaimedRewards`].
+	ClaimedRewards,
+	/// [`ErasExposures`].
+	Exposures,
+	/// [`ErasCommissions`].
+	Commissions,
+	/// [`ErasSlashes`].
+	Slashes,
+	/// [`ErasSlashFractions`].
+	SlashFractions,
+}
+
+impl PruningStage {
+	/// The stage after this one, `None` once the era is fully pruned.
+	fn next(self) -> Option<Self> {
+		match self {
+			PruningStage::Values => Some(PruningStage::ClaimedRewards),
+			PruningStage::ClaimedRewards => Some(PruningStage::Exposures),
+			PruningStage::Exposures => Some(PruningStage::Commissions),
+			PruningStage::Commissions => Some(PruningStage::Slashes),
+			PruningStage::Slashes => Some(PruningStage::SlashFractions),
+			PruningStage::SlashFractions => None,
+		}
+	}
+}
+
+/// The length of the longest key of the double maps pruned per era.
+///
+/// The keys are the pallet and storage prefixes, followed by the `Twox64Concat` hashed era and
+/// account.
+pub struct MaxCursorLen<T>(PhantomData<T>);
+
+impl<T: Config> Get<u32> for MaxCursorLen<T> {
+	fn get() -> u32 {
+		let prefixes = 32;
+		let era = 8 + EraIndex::max_encoded_len();
+		let account = 8 + T::AccountId::max_encoded_len();
+		(prefixes + era + account).saturated_into()
+	}
+}
+
+/// Where the pruning of the history is at.
+#[derive(
+	CloneNoBound,
+	DefaultNoBound,
+	PartialEqNoBound,
+	EqNoBound,
+	Encode,
+	Decode,
+	RuntimeDebugNoBound,
+	TypeInfo,
+	MaxEncodedLen,
+)]
+#[scale_info(skip_type_params(T))]
+pub struct PruningProgress<T: Config> {
+	/// The oldest era not fully pruned yet.
+	pub era: EraIndex,
+	/// The storage items of `era` being pruned.
+	pub stage: PruningStage,
+	/// The cursor to resume clearing the current double map of `era` from.
+	pub cursor: Option<BoundedVec<u8, MaxCursorLen<T>>>,
+}
+
+impl<T: Config> Pallet<T> {
+	/// Prune the eras out of history, using at most `remaining_weight`.
+	///
+	/// Returns the weight used.
+	pub(crate) fn prune_history(remaining_weight: Weight) -> Weight {
+		let db_weight = T::DbWeight::get();
+		// Reading the era, the config and the progress, and writing the progress back.
+		let mut used = db_weight.reads_writes(3, 1);
+		if remaining_weight.any_lt(used) {
+			return Weight::zero();
+		}
+
+		if Self::current_era().is_none() {
+			return used;
+		}
+		let prune_below = Self::oldest_era_in_history();
+
+		let mut progress = Self::history_pruning();
+		if progress.era >= prune_below {
+			return used;
+		}
+
+		let value_weight = db_weight.writes(10);
+		let key_weight = Self::pruned_key_weight();
+		while progress.era < prune_below {
+			if progress.stage == PruningStage::Values {
+				if remaining_weight.any_lt(used.saturating_add(value_weight)) {
+					break;
+				}
+				Self::clear_era_values(progress.era);
+				used.saturating_accrue(value_weight);
+			} else {
+				let left = remaining_weight.saturating_sub(used);
+				let limit = (left.ref_time() / key_weight.ref_time().max(1))
+					.min(left.proof_size() / key_weight.proof_size().max(1))
+					.min(u32::MAX.into()) as u32;
+				if limit == 0 {
+					break;
+				}
+
+				let cursor = progress.cursor.take();
+				let result =
+					Self::clear_era_entries(progress.stage, progress.era, limit, cursor.as_deref());
+				used.saturating_accrue(
+					db_weight
+						.reads_writes(result.loops.into(), result.unique.into())
+						.saturating_add(Weight::from_parts(
+							0,
+							key_weight.proof_size().saturating_mul(result.loops.into()),
+						)),
+				);
+				if let Some(cursor) = result.maybe_cursor {
+					// The cursor is a key of the map, it always fits. Should it not, the map is
+					// cleared again from its start.
+					progress.cursor = BoundedVec::try_from(cursor)
+						.map_err(|_| defensive!("The pruning cursor is longer than a key"))
+						.ok();
+					break;
+				}
+			}
+
+			match progress.stage.next() {
+				Some(stage) => progress.stage = stage,
+				None => {
+					progress.era = progress.era.saturating_add(1);
+					progress.stage = PruningStage::Values;
+				},
+			}
+		}
+
+		HistoryPruning::<T>::put(progress);
+		used
+	}
+
+	/// The weight of removing one key of a double map pruned per era.
+	pub(crate) fn pruned_key_weight() -> Weight {
+		T::DbWeight::get()
+			.reads_writes(1, 1)
+			.saturating_add(Weight::from_parts(0, MaxCursorLen::<T>::get().into()))
+	}
+
+	/// The oldest era kept in history, the eras before it are pruned.
+	///
+	/// The eras kept in history are `[active_era - history_depth, active_era]` and the planned
+	/// era, so the era which just ended stays in history even with a `history_depth` of 1.
+	pub(crate) fn oldest_era_in_history() -> EraIndex {
+		Self::active_era()
+			.map_or(0, |era| era.index)
+			.saturating_sub(Self::current_config().history_depth)
+	}
+
+	/// Remove the values recorded for `era` in the maps with a single value per era.
+	fn clear_era_values(era: EraIndex) {
+		ErasStartSessionIndex::<T>::remove(era);
+		ErasSequencers::<T>::remove(era);
+		ErasSequencerGroups::<T>::remove(era);
+		ErasStandbySequencers::<T>::remove(era);
+		ErasGroupSeed::<T>::remove(era);
+		ErasTotalStake::<T>::remove(era);
+		ErasRewardPoints::<T>::remove(era);
+		ErasSequencerReward::<T>::remove(era);
+		ErasStartTimestamp::<T>::remove(era);
+		ErasDuration::<T>::remove(era);
+	}
+
+	/// Remove at most `limit` entries of `era` out of the double map of `stage`.
+	fn clear_era_entries(
+		stage: PruningStage,
+		era: EraIndex,
+		limit: u32,
+		cursor: Option<&[u8]>,
+	) -> MultiRemovalResults {
+		match stage {
+			PruningStage::Values => MultiRemovalResults::default(),
+			PruningStage::ClaimedRewards => ClaimedRewards::<T>::clear_prefix(era, limit, cursor),
+			PruningStage::Exposures => ErasExposures::<T>::clear_prefix(era, limit, cursor),
+			PruningStage::Commissions => ErasCommissions::<T>::clear_prefix(era, limit, cursor),
+			PruningStage::Slashes => ErasSlashes::<T>::clear_prefix(era, limit, cursor),
+			PruningStage::SlashFractions =>
+				ErasSlashFractions::<T>::clear_prefix(era, limit, cursor),
+		}
+	}
+}
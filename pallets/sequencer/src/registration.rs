@@ -0,0 +1,128 @@
+//! Registration of the sequencer operators.
+//!
+//! Only registered operators are eligible in the elections. An operator registers with a
+//! [`SequencerInfo`] describing it and the endpoints rollup clients can reach it at. The info is
+//! recorded in [`Registrations`], for anyone to look the endpoints up.
+//!
+//! Registering takes a storage deposit of [`Config::RegistrationDepositBase`] plus
+//! [`Config::RegistrationDepositPerByte`] for every byte of the encoded info, held on
+//! [`Config::Currency`] until the operator deregisters.
+
+use super::*;
+
+/// Bytes of an info field.
+pub type InfoField<T> = BoundedVec<u8, <T as Config>::MaxFieldLength>;
+
+/// Endpoints of an operator, as multiaddrs.
+pub type Endpoints<T> = BoundedVec<InfoField<T>, <T as Config>::MaxEndpoints>;
+
+/// Description of a sequencer operator.
+#[derive(
+	PartialEqNoBound,
+	EqNoBound,
+	CloneNoBound,
+	Encode,
+	Decode,
+	RuntimeDebugNoBound,
+	TypeInfo,
+	MaxEncodedLen,
+)]
+#[scale_info(skip_type_params(T))]
+pub struct SequencerInfo<T: Config> {
+	/// Name the operator is displayed with.
+	pub display_name: InfoField<T>,
+	/// Multiaddrs of the RPC endpoints.
+	pub rpc_endpoints: Endpoints<T>,
+	/// Multiaddrs of the P2P endpoints.
+	pub p2p_endpoints: Endpoints<T>,
+	/// How to contact the operator, e.g. an email address.
+	pub contact: InfoField<T>,
+}
+
+/// A registered operator.
+#[derive(
+	PartialEqNoBound,
+	EqNoBound,
+	CloneNoBound,
+	Encode,
+	Decode,
+	RuntimeDebugNoBound,
+	TypeInfo,
+	MaxEncodedLen,
+)]
+#[scale_info(skip_type_params(T))]
+pub struct Registration<T: Config> {
+	/// The description of the operator.
+	pub info: SequencerInfo<T>,
+	/// The deposit held for the registration.
+	pub deposit: BalanceOf<T>,
+}
+
+impl<T: Config> Pallet<T> {
+	/// Register `operator` with `info` and `commission`, holding the deposit.
+	pub fn do_register_sequencer(
+		operator: T::AccountId,
+		info: SequencerInfo<T>,
+		commission: Perbill,
+	) -> DispatchResult {
+		ensure!(!Registrations::<T>::contains_key(&operator), Error::<T>::AlreadyRegistered);
+
+		let deposit = Self::registration_deposit(&info);
+		T::Currency::hold(&HoldReason::Registration.into(), &operator, deposit)?;
+		Registrations::<T>::insert(&operator, Registration { info, deposit });
+		Commissions::<T>::insert(&operator, commission);
+
+		Self::deposit_event(Event::<T>::SequencerRegistered { operator, deposit, commission });
+		Ok(())
+	}
+
+	/// Replace the info of `operator`, adjusting the deposit to the new info.
+	pub fn do_update_sequencer(operator: T::AccountId, info: SequencerInfo<T>) -> DispatchResult {
+		let registration = Self::registrations(&operator).ok_or(Error::<T>::NotRegistered)?;
+
+		let deposit = Self::registration_deposit(&info);
+		if deposit > registration.deposit {
+			T::Currency::hold(
+				&HoldReason::Registration.into(),
+				&operator,
+				deposit.saturating_sub(registration.deposit),
+			)?;
+		} else if deposit < registration.deposit {
+			T::Currency::release(
+				&HoldReason::Registration.into(),
+				&operator,
+				registration.deposit.saturating_sub(deposit),
+				Precision::BestEffort,
+			)?;
+		}
+		Registrations::<T>::insert(&operator, Registration { info, deposit });
+
+		Self::deposit_event(Event::<T>::SequencerUpdated { operator, deposit });
+		Ok(())
+	}
+
+	/// Deregister `operator`, releasing the deposit and forgetting its commission.
+	///
+	/// It is no longer eligible from the next election on, the delegations to it are kept until
+	/// the delegators undelegate.
+	pub fn do_deregister_sequencer(operator: T::AccountId) -> DispatchResult {
+		let registration = Registrations::<T>::take(&operator).ok_or(Error::<T>::NotRegistered)?;
+		Commissions::<T>::remove(&operator);
+		T::Currency::release(
+			&HoldReason::Registration.into(),
+			&operator,
+			registration.deposit,
+			Precision::BestEffort,
+		)?;
+
+		Self::deposit_event(Event::<T>::SequencerDeregistered { operator });
+		Ok(())
+	}
+
+	/// The deposit held for registering `info`.
+	pub fn registration_deposit(info: &SequencerInfo<T>) -> BalanceOf<T> {
+		let bytes = BalanceOf::<T>::from(info.encoded_size() as u32);
+		T::RegistrationDepositBase::get()
+			.saturating_add(T::RegistrationDepositPerByte::get().saturating_mul(bytes))
+	}
+}
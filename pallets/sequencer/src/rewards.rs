@@ -0,0 +1,185 @@
+//! Era rewards of the sequencers.
+//!
+//! During an era, the sequencers earn reward points through [`RewardSequencers`], recorded in
+//! [`ErasRewardPoints`]. When the era ends, [`Config::RewardSource`] decides how much the era
+//! pays, recorded in [`ErasSequencerReward`].
+//!
+//! Anyone can then call `payout_era` for an elected sequencer, as long as the era has not fallen
+//! out of history, counted from the active era like the pruning.
+//! [`Config::PointsRewardPortion`] of the era reward is split in proportion to the points, the
+//! rest in proportion to the stake the sequencers were elected with. The share of a sequencer is
+//! then split between the operator and its delegators, see [`Pallet::do_payout_era`].
+
+use super::*;
+use frame_support::traits::tokens::Preservation;
+use sp_runtime::traits::SaturatedConversion;
+
+/// Number of milliseconds in a year, of 365.25 days.
+const MILLISECONDS_PER_YEAR: u64 = 1000 * 3600 * 24 * 36525 / 100;
+
+/// Source of the era rewards.
+pub trait EraRewardSource<AccountId, Balance> {
+	/// The reward of an era which lasted `era_duration_millis`, `total_stake` being elected.
+	fn era_reward(total_stake: Balance, era_duration_millis: u64) -> Balance;
+
+	/// Pay `amount` of reward to `who`.
+	fn pay(who: &AccountId, amount: Balance) -> DispatchResult;
+}
+
+/// No reward at all.
+impl<AccountId, Balance: Zero> EraRewardSource<AccountId, Balance> for () {
+	fn era_reward(_total_stake: Balance, _era_duration_millis: u64) -> Balance {
+		Zero::zero()
+	}
+
+	fn pay(_who: &AccountId, _amount: Balance) -> DispatchResult {
+		Ok(())
+	}
+}
+
+/// Pay a fixed `EraReward` per era out of the `Pot` account.
+///
+/// The payouts fail once the pot runs dry, until it is refilled.
+pub struct RewardPot<Currency, Pot, EraReward>(
+	sp_std::marker::PhantomData<(Currency, Pot, EraReward)>,
+);
+
+impl<AccountId, Currency, Pot, EraReward> EraRewardSource<AccountId, Currency::Balance>
+	for RewardPot<Currency, Pot, EraReward>
+where
+	Currency: fungible::Mutate<AccountId>,
+	Pot: Get<AccountId>,
+	EraReward: Get<Currency::Balance>,
+{
+	fn era_reward(_total_stake: Currency::Balance, _era_duration_millis: u64) -> Currency::Balance {
+		EraReward::get()
+	}
+
+	fn pay(who: &AccountId, amount: Currency::Balance) -> DispatchResult {
+		Currency::transfer(&Pot::get(), who, amount, Preservation::Preserve).map(|_| ())
+	}
+}
+
+/// Mint the rewards, `YearlyInflation` of the elected stake per year.
+pub struct Minted<Currency, YearlyInflation>(
+	sp_std::marker::PhantomData<(Currency, YearlyInflation)>,
+);
+
+impl<AccountId, Currency, YearlyInflation> EraRewardSource<AccountId, Currency::Balance>
+	for Minted<Currency, YearlyInflation>
+where
+	Currency: fungible::Mutate<AccountId>,
+	YearlyInflation: Get<Perbill>,
+{
+	fn era_reward(total_stake: Currency::Balance, era_duration_millis: u64) -> Currency::Balance {
+		let yearly_reward = YearlyInflation::get() * total_stake;
+		Perbill::from_rational(
+			era_duration_millis.min(MILLISECONDS_PER_YEAR),
+			MILLISECONDS_PER_YEAR,
+		) * yearly_reward
+	}
+
+	fn pay(who: &AccountId, amount: Currency::Balance) -> DispatchResult {
+		Currency::mint_into(who, amount).map(|_| ())
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// Record the duration of the era ending and compute its reward from it.
+	///
+	/// Nothing is recorded nor paid for an era which never got a start time.
+	pub(crate) fn end_era(active_era: ActiveEraInfo) {
+		let Some(start) = active_era.start else { return };
+		let now = T::UnixTime::now().as_millis().saturated_into::<u64>();
+		let era_duration = now.saturating_sub(start);
+		ErasDuration::<T>::insert(active_era.index, era_duration);
+
+		let total_stake = Self::eras_total_stake(active_era.index);
+		let reward = T::RewardSource::era_reward(total_stake, era_duration);
+		ErasSequencerReward::<T>::insert(active_era.index, reward);
+		Self::deposit_event(Event::<T>::EraPaid { era: active_era.index, reward });
+	}
+
+	/// Pay `sequencer` its share of the reward of `era`.
+	///
+	/// The operator takes its commission out of the share, the rest is split between the operator
+	/// and its delegators in proportion to the stake they backed the operator with in `era`.
+	pub fn do_payout_era(era: EraIndex, sequencer: T::AccountId) -> DispatchResult {
+		ensure!(era >= Self::oldest_era_in_history(), Error::<T>::InvalidEra);
+		let era_reward = Self::eras_sequencer_reward(era).ok_or(Error::<T>::InvalidEra)?;
+		ensure!(!ClaimedRewards::<T>::get(era, &sequencer), Error::<T>::AlreadyClaimed);
+
+		let elected = Self::eras_sequencers(era)
+			.into_iter()
+			.find(|elected| elected.who == sequencer)
+			.ok_or(Error::<T>::NotElected)?;
+		let points = Self::eras_reward_points(era);
+		let total_stake = Self::eras_total_stake(era);
+
+		let points_reward = T::PointsRewardPortion::get() * era_reward;
+		let stake_reward = era_reward.saturating_sub(points_reward);
+
+		let mut amount: BalanceOf<T> = Zero::zero();
+		if points.total > 0 {
+			let sequencer_points = points.individual.get(&sequencer).copied().unwrap_or(0);
+			amount = amount.saturating_add(
+				Perbill::from_rational(sequencer_points, points.total) * points_reward,
+			);
+		}
+		if !total_stake.is_zero() {
+			amount = amount
+				.saturating_add(Perbill::from_rational(elected.stake, total_stake) * stake_reward);
+		}
+
+		ClaimedRewards::<T>::insert(era, &sequencer, true);
+
+		let exposure = Self::eras_exposures(era, &sequencer);
+		let commission = Self::eras_commissions(era, &sequencer) * amount;
+		let leftover = amount.saturating_sub(commission);
+
+		let own_reward = Perbill::from_rational(exposure.own, exposure.total) * leftover;
+		Self::pay_reward(
+			sequencer.clone(),
+			&sequencer,
+			era,
+			commission.saturating_add(own_reward),
+		)?;
+		for delegator in exposure.others {
+			let reward = Perbill::from_rational(delegator.value, exposure.total) * leftover;
+			Self::pay_reward(delegator.who, &sequencer, era, reward)?;
+		}
+		Ok(())
+	}
+
+	/// Pay `who` its `amount` of the reward of `sequencer` for `era`.
+	fn pay_reward(
+		who: T::AccountId,
+		sequencer: &T::AccountId,
+		era: EraIndex,
+		amount: BalanceOf<T>,
+	) -> DispatchResult {
+		if !amount.is_zero() {
+			T::RewardSource::pay(&who, amount)?;
+		}
+		Self::deposit_event(Event::<T>::Rewarded {
+			who,
+			sequencer: sequencer.clone(),
+			era,
+			amount,
+		});
+		Ok(())
+	}
+}
+
+impl<T: Config> RewardSequencers<T::AccountId> for Pallet<T> {
+	fn reward_by_ids(sequencers_points: impl IntoIterator<Item = (T::AccountId, u32)>) {
+		let Some(active_era) = Self::active_era() else { return };
+		ErasRewardPoints::<T>::mutate(active_era.index, |era_points| {
+			for (sequencer, points) in sequencers_points.into_iter() {
+				let individual = era_points.individual.entry(sequencer).or_default();
+				*individual = individual.saturating_add(points);
+				era_points.total = era_points.total.saturating_add(points);
+			}
+		});
+	}
+}
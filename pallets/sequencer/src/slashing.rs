@@ -0,0 +1,240 @@
+//! Slashing of the sequencers.
+//!
+//! An offence committed by a sequencer during an era is reported either by
+//! [`Config::AdminOrigin`] through `report_offence`, or by the offences machinery through
+//! [`OnOffenceHandler`]. The slash amounts are computed right away out of the stake backing the
+//! sequencer when it was elected, recorded in [`ErasExposures`]: the operator and its delegators
+//! are slashed the same fraction of their stake. A sequencer is only slashed once per era, for
+//! the highest fraction reported, recorded in [`ErasSlashFractions`]: a higher fraction only
+//! slashes the difference. The slash is only applied [`Config::SlashDeferDuration`] eras later.
+//! Until then it can be cancelled by [`Config::AdminOrigin`] through `cancel_deferred_slash`.
+//!
+//! Slashed funds are taken out of the bonded or delegated funds first, then out of the unlocking
+//! chunks, latest first, and handed over to [`Config::Slash`]. Every applied slash is recorded in
+//! [`ErasSlashes`].
+
+use super::*;
+use frame_support::{defensive, pallet_prelude::Weight};
+use sp_staking::offence::{DisableStrategy, OffenceDetails, OnOffenceHandler};
+
+/// A slash waiting to be applied.
+#[derive(
+	CloneNoBound,
+	PartialEqNoBound,
+	EqNoBound,
+	Encode,
+	Decode,
+	RuntimeDebugNoBound,
+	TypeInfo,
+	MaxEncodedLen,
+)]
+#[scale_info(skip_type_params(T))]
+pub struct UnappliedSlash<T: Config> {
+	/// The slashed sequencer.
+	pub sequencer: T::AccountId,
+	/// The era the offence was committed in.
+	pub era: EraIndex,
+	/// The amount to slash from the operator's own stake.
+	pub own: BalanceOf<T>,
+	/// The amounts to slash from the delegators.
+	pub others: BoundedVec<(T::AccountId, BalanceOf<T>), T::MaxDelegators>,
+}
+
+impl<T: Config> Pallet<T> {
+	/// Queue a slash of `fraction` of the stake `sequencer` was elected with in `era`.
+	///
+	/// `era` must have started and still be in history. The slash is applied at the start of the
+	/// era `current_era + SlashDeferDuration`, counted from the latest planned era, right away if
+	/// there is no defer duration. Only the part of `fraction` above the highest fraction already
+	/// reported for `sequencer` in `era` is slashed.
+	pub fn do_report_offence(
+		era: EraIndex,
+		sequencer: T::AccountId,
+		fraction: Perbill,
+	) -> DispatchResult {
+		let active_era = Self::active_era().map(|info| info.index).unwrap_or(0);
+		ensure!(era <= active_era, Error::<T>::InvalidEra);
+		ensure!(era >= Self::oldest_era_in_history(), Error::<T>::InvalidEra);
+
+		ensure!(ErasExposures::<T>::contains_key(era, &sequencer), Error::<T>::NotElected);
+		let reported = Self::eras_slash_fractions(era, &sequencer);
+		if fraction <= reported {
+			return Ok(());
+		}
+
+		let exposure = Self::eras_exposures(era, &sequencer);
+		let slash_of = |value: BalanceOf<T>| (fraction * value).saturating_sub(reported * value);
+		let own = slash_of(exposure.own);
+		let others = exposure
+			.others
+			.into_iter()
+			.map(|delegator| (delegator.who, slash_of(delegator.value)))
+			.collect::<Vec<_>>();
+		let others = BoundedVec::try_from(others).map_err(|_| Error::<T>::TooManyDelegators)?;
+		let amount = others.iter().fold(own, |total, (_, value)| total.saturating_add(*value));
+		let slash = UnappliedSlash { sequencer: sequencer.clone(), era, own, others };
+
+		let defer_duration = T::SlashDeferDuration::get();
+		let apply_era = Self::current_era().unwrap_or(0).saturating_add(defer_duration);
+		if defer_duration.is_zero() {
+			Self::apply_slash(slash);
+		} else {
+			UnappliedSlashes::<T>::try_append(apply_era, slash)
+				.map_err(|_| Error::<T>::TooManySlashes)?;
+		}
+		ErasSlashFractions::<T>::insert(era, &sequencer, fraction);
+
+		Self::deposit_event(Event::<T>::SlashReported {
+			sequencer,
+			era,
+			fraction,
+			amount,
+			apply_era,
+		});
+		Ok(())
+	}
+
+	/// Cancel the slashes queued for `apply_era` at `slash_indices`.
+	///
+	/// `slash_indices` must be sorted and free of duplicates.
+	pub fn do_cancel_deferred_slash(
+		apply_era: EraIndex,
+		slash_indices: Vec<u32>,
+	) -> DispatchResult {
+		ensure!(!slash_indices.is_empty(), Error::<T>::EmptyTargets);
+		ensure!(
+			slash_indices.windows(2).all(|pair| pair[0] < pair[1]),
+			Error::<T>::NotSortedAndUnique
+		);
+
+		let mut unapplied = UnappliedSlashes::<T>::get(apply_era);
+		let last = *slash_indices.last().expect("checked not empty above; qed");
+		ensure!((last as usize) < unapplied.len(), Error::<T>::InvalidSlashIndex);
+
+		// Remove from the back so the lower indices stay valid.
+		for index in slash_indices.into_iter().rev() {
+			let slash = unapplied.remove(index as usize);
+			Self::deposit_event(Event::<T>::SlashCancelled {
+				sequencer: slash.sequencer,
+				era: slash.era,
+				apply_era,
+			});
+		}
+
+		if unapplied.is_empty() {
+			UnappliedSlashes::<T>::remove(apply_era);
+		} else {
+			UnappliedSlashes::<T>::insert(apply_era, unapplied);
+		}
+		Ok(())
+	}
+
+	/// Apply all the slashes queued for `era`.
+	pub(crate) fn apply_unapplied_slashes(era: EraIndex) {
+		for slash in UnappliedSlashes::<T>::take(era) {
+			Self::apply_slash(slash);
+		}
+	}
+
+	/// Slash the operator and the delegators of a sequencer.
+	fn apply_slash(slash: UnappliedSlash<T>) {
+		let UnappliedSlash { sequencer, era, own, others } = slash;
+		Self::slash_staker(sequencer.clone(), &sequencer, era, own);
+		for (delegator, amount) in others {
+			Self::slash_staker(delegator, &sequencer, era, amount);
+		}
+	}
+
+	/// Slash `amount` of the stake `who` backs `sequencer` with, then of its unlocking funds.
+	fn slash_staker(
+		who: T::AccountId,
+		sequencer: &T::AccountId,
+		era: EraIndex,
+		amount: BalanceOf<T>,
+	) {
+		let mut remaining = amount;
+
+		if who == *sequencer {
+			let bonded = Self::restake_data(&who);
+			let slashed = remaining.min(bonded);
+			Self::set_bonded(&who, bonded.saturating_sub(slashed));
+			remaining = remaining.saturating_sub(slashed);
+		} else {
+			let slashed = remaining.min(Self::delegations(sequencer, &who));
+			Self::reduce_delegation(sequencer, &who, slashed);
+			remaining = remaining.saturating_sub(slashed);
+		}
+
+		if !remaining.is_zero() {
+			Unlocking::<T>::mutate_exists(&who, |maybe_chunks| {
+				if let Some(chunks) = maybe_chunks {
+					for chunk in chunks.iter_mut().rev() {
+						let slashed = remaining.min(chunk.value);
+						chunk.value = chunk.value.saturating_sub(slashed);
+						remaining = remaining.saturating_sub(slashed);
+					}
+					chunks.retain(|chunk| !chunk.value.is_zero());
+					if chunks.is_empty() {
+						*maybe_chunks = None;
+					}
+				}
+			});
+		}
+
+		let (credit, missing) = T::Currency::slash(
+			&HoldReason::Restaking.into(),
+			&who,
+			amount.saturating_sub(remaining),
+		);
+		if !missing.is_zero() {
+			defensive!("held funds should always cover the bonded and unlocking funds");
+		}
+		T::Slash::on_unbalanced(credit);
+
+		let slashed = amount.saturating_sub(remaining).saturating_sub(missing);
+		ErasSlashes::<T>::mutate(era, &who, |total| *total = total.saturating_add(slashed));
+		Self::deposit_event(Event::<T>::Slashed {
+			who,
+			sequencer: sequencer.clone(),
+			era,
+			amount: slashed,
+		});
+	}
+
+	/// The era `session` belongs to, if it is still in history.
+	fn era_of_session(session: SessionIndex) -> Option<EraIndex> {
+		let current_era = Self::current_era()?;
+		(Self::oldest_era_in_history()..=current_era)
+			.rev()
+			.find(|era| Self::eras_start_session_index(era).map_or(false, |start| start <= session))
+	}
+}
+
+impl<T: Config, FullIdentification>
+	OnOffenceHandler<T::AccountId, (T::AccountId, FullIdentification), Weight> for Pallet<T>
+{
+	fn on_offence(
+		offenders: &[OffenceDetails<T::AccountId, (T::AccountId, FullIdentification)>],
+		slash_fraction: &[Perbill],
+		session: SessionIndex,
+		_disable_strategy: DisableStrategy,
+	) -> Weight {
+		let db_weight = T::DbWeight::get();
+		let history_depth = u64::from(Self::current_config().history_depth);
+		let Some(era) = Self::era_of_session(session) else {
+			return db_weight.reads(history_depth.saturating_add(2));
+		};
+
+		for (details, fraction) in offenders.iter().zip(slash_fraction) {
+			let (sequencer, _) = &details.offender;
+			if let Err(e) = Self::do_report_offence(era, sequencer.clone(), *fraction) {
+				log::warn!("Could not slash sequencer {:?} for era {}: {:?}", sequencer, era, e);
+			}
+		}
+
+		db_weight.reads_writes(
+			history_depth.saturating_add(2).saturating_add(offenders.len() as u64 * 4),
+			offenders.len() as u64 * 5,
+		)
+	}
+}
@@ -0,0 +1,97 @@
+//! Aggregates of the stakers and their sorted list.
+//!
+//! Every change of a bond goes through [`Pallet::set_bonded`], which keeps [`TotalRestake`] and
+//! [`RestakerCount`] up to date, so they never have to be computed over [`RestakeData`].
+//!
+//! The stakers are also kept in [`Config::StakerList`], sorted by their election weight, their
+//! own bond plus the delegations to them. The elections only read the top of the list.
+
+use super::*;
+use frame_election_provider_support::ScoreProvider;
+use frame_support::defensive;
+
+/// How many times [`Config::MaxCandidates`] stakers are read from [`Config::StakerList`] at most
+/// to find the candidates, see [`Pallet::top_candidates`].
+pub(crate) const CANDIDATE_SCAN_FACTOR: u32 = 2;
+
+impl<T: Config> Pallet<T> {
+	/// Set the bond of `who` to `bonded`, removing the staker if it is zero.
+	pub(crate) fn set_bonded(who: &T::AccountId, bonded: BalanceOf<T>) {
+		let previous = RestakeData::<T>::get(who);
+		if previous == bonded {
+			return;
+		}
+
+		TotalRestake::<T>::mutate(|total| {
+			*total = total.saturating_sub(previous).saturating_add(bonded)
+		});
+		if previous.is_zero() {
+			RestakerCount::<T>::mutate(|count| *count = count.saturating_add(1));
+		} else if bonded.is_zero() {
+			RestakerCount::<T>::mutate(|count| *count = count.saturating_sub(1));
+		}
+
+		if bonded.is_zero() {
+			RestakeData::<T>::remove(who);
+		} else {
+			RestakeData::<T>::insert(who, bonded);
+		}
+		Self::update_staker_list(who);
+	}
+
+	/// Move `who` in [`Config::StakerList`] to its current election weight.
+	///
+	/// Only bonded accounts are in the list, the delegations to an account which does not bond
+	/// itself do not get it in.
+	pub(crate) fn update_staker_list(who: &T::AccountId) {
+		let result = if RestakeData::<T>::contains_key(who) {
+			let weight = Self::election_weight(who);
+			if T::StakerList::contains(who) {
+				T::StakerList::on_update(who, weight)
+			} else {
+				T::StakerList::on_insert(who.clone(), weight)
+			}
+		} else if T::StakerList::contains(who) {
+			T::StakerList::on_remove(who)
+		} else {
+			Ok(())
+		};
+
+		if let Err(e) = result {
+			defensive!("the staker list is out of sync with the bonds", e);
+		}
+	}
+
+	/// The best [`Config::MaxCandidates`] stakers which are registered operators and can
+	/// validate, by election weight.
+	///
+	/// The stakers which are not registered or can not validate are skipped, but only the first
+	/// [`Pallet::max_candidate_scan`] stakers of the list are read, so the stakers ranked below
+	/// them are not candidates even if too few were found.
+	pub(crate) fn top_candidates() -> Vec<T::AccountId> {
+		let scanned = Self::max_candidate_scan().min(T::StakerList::count());
+		Self::register_weight(T::WeightInfo::top_candidates(scanned));
+
+		T::StakerList::iter()
+			.take(Self::max_candidate_scan() as usize)
+			.filter(|who| {
+				Registrations::<T>::contains_key(who) &&
+					T::ValidatorRegistration::is_registered(who)
+			})
+			.take(T::MaxCandidates::get() as usize)
+			.collect()
+	}
+
+	/// The number of stakers read from [`Config::StakerList`] at most to find the candidates.
+	pub fn max_candidate_scan() -> u32 {
+		T::MaxCandidates::get().saturating_mul(CANDIDATE_SCAN_FACTOR)
+	}
+}
+
+impl<T: Config> ScoreProvider<T::AccountId> for Pallet<T> {
+	type Score = BalanceOf<T>;
+
+	fn score(who: &T::AccountId) -> Self::Score {
+		Self::election_weight(who)
+	}
+}
@@ -1,23 +1,1356 @@
-use crate::{mock::*, Error};
-use frame_support::{assert_noop, assert_ok};
+use crate::{
+	mock::*, BalancingConfig, ElectionError, EraConfig, ErasCommissions, ErasExposures, Error,
+	Event, Forcing, GreedyBalance, GroupAssignment, HoldReason, PruningStage, RewardSequencers,
+	SequencerElection, SequentialPhragmen, SnakeDraft, StakeGraph, StakeWeightedSampling,
+	Threshold,
+};
+use ep_sequencer::{ActiveEraInfo, EraTiming, Exposure};
+use frame_election_provider_support::SortedListProvider;
+use frame_support::{
+	assert_noop, assert_ok, parameter_types,
+	traits::{
+		fungible::InspectHold, Get, GetStorageVersion, OnRuntimeUpgrade, Randomness, StorageVersion,
+	},
+	weights::Weight,
+};
+use sp_core::{ConstU32, H256};
+use sp_runtime::{DispatchError, Perbill};
+use sp_staking::offence::{DisableStrategy, OffenceDetails, OnOffenceHandler};
+
+parameter_types! {
+	static SamplingSeed: H256 = H256::zero();
+	NoBalancing: Option<BalancingConfig> = None;
+	Balanced: Option<BalancingConfig> = Some(BalancingConfig { iterations: 10, tolerance: 0 });
+}
+
+type Phragmen<Balancing> =
+	SequentialPhragmen<Balances, sp_staking::currency_to_vote::U128CurrencyToVote, Balancing>;
+
+/// Elect the exposures out of `graph` with Phragmén, balanced by `Balancing`.
+fn phragmen<Balancing: Get<Option<BalancingConfig>>>(
+	graph: StakeGraph<u64, u128>,
+	seats: u32,
+) -> Result<Vec<(u64, Exposure<u64, u128>)>, ElectionError> {
+	<Phragmen<Balancing> as SequencerElection<u64, u128>>::elect_exposures::<ConstU32<4>>(
+		graph, seats, 1,
+	)
+	.map(|exposures| exposures.into_inner())
+}
+
+/// Randomness returning [`SamplingSeed`], whatever the subject.
+struct SeededRandomness;
+
+impl Randomness<H256, u64> for SeededRandomness {
+	fn random(_subject: &[u8]) -> (H256, u64) {
+		(SamplingSeed::get(), 0)
+	}
+}
+
+/// Sample `seats` out of `candidates` with `seed`.
+fn sample(seed: u8, candidates: &[(u64, u128)], seats: u32) -> Vec<u64> {
+	SamplingSeed::set(H256::repeat_byte(seed));
+	<StakeWeightedSampling<Test, SeededRandomness> as SequencerElection<u64, u128>>::elect::<
+		ConstU32<16>,
+	>(candidates.to_vec(), seats, 1)
+	.unwrap()
+	.into_iter()
+	.map(|(who, _)| who)
+	.collect()
+}
+
+/// The elected sequencers of `era`, by rank.
+fn elected(era: u32) -> Vec<u64> {
+	SequencerModule::eras_sequencers(era)
+		.into_iter()
+		.map(|sequencer| sequencer.who)
+		.collect()
+}
+
+/// The total stake of every group.
+fn group_totals(groups: &[Vec<(u64, u128)>]) -> Vec<u128> {
+	groups.iter().map(|group| group.iter().map(|(_, stake)| stake).sum()).collect()
+}
+
+/// Pay out the reward of `sequencer` for `era`, returning how much each account got.
+fn payout(era: u32, sequencer: u64, accounts: &[u64]) -> Vec<u128> {
+	let before = accounts.iter().map(Balances::free_balance).collect::<Vec<_>>();
+	assert_ok!(SequencerModule::payout_era(RuntimeOrigin::signed(1), era, sequencer));
+	accounts
+		.iter()
+		.zip(before)
+		.map(|(who, before)| Balances::free_balance(who) - before)
+		.collect()
+}
+
+/// Check that the aggregates and the sorted list of the stakers match their bonds.
+fn assert_stakers_in_sync() {
+	let bonds = crate::RestakeData::<Test>::iter().collect::<Vec<_>>();
+	assert_eq!(
+		SequencerModule::total_restake(),
+		bonds.iter().map(|(_, bonded)| bonded).sum::<u128>()
+	);
+	assert_eq!(SequencerModule::restaker_count(), bonds.len() as u32);
+
+	let mut listed = <StakerList as SortedListProvider<u64>>::iter().collect::<Vec<_>>();
+	listed.sort();
+	let mut bonded = bonds.iter().map(|(who, _)| *who).collect::<Vec<_>>();
+	bonded.sort();
+	assert_eq!(listed, bonded);
+	for who in listed {
+		assert_eq!(
+			<StakerList as SortedListProvider<u64>>::get_score(&who).ok(),
+			Some(SequencerModule::election_weight(&who))
+		);
+	}
+}
+
+/// Bond `amount` for `who` and register it as an operator.
+fn bond_and_register(who: u64, amount: u128) {
+	assert_ok!(SequencerModule::bond(RuntimeOrigin::signed(who), amount));
+	assert_ok!(SequencerModule::register_sequencer(
+		RuntimeOrigin::signed(who),
+		empty_info(),
+		Perbill::zero()
+	));
+}
+
+#[test]
+fn only_the_top_of_the_staker_list_is_scanned_for_candidates() {
+	BagThresholds::set(&[1_000, 2_000, 3_000, 4_000, 5_000, 6_000, 7_000, 8_000]);
+	new_test_ext().execute_with(|| {
+		// Only the best 4 stakers are scanned.
+		MaxCandidates::set(2);
+		assert_eq!(SequencerModule::max_candidate_scan(), 4);
+
+		// Stakers ranked above the operators, but not registered.
+		for (who, bond) in [(1, 8_000), (2, 7_000), (3, 6_000)] {
+			assert_ok!(SequencerModule::bond(RuntimeOrigin::signed(who), bond));
+		}
+		assert_eq!(StakerList::iter().collect::<Vec<_>>(), vec![1, 2, 3, 41, 31, 21, 11]);
+		assert_eq!(SequencerModule::api_restake_totals(), (31_000, 7));
+
+		// 31 is left out even though fewer than `MaxCandidates` were found.
+		assert_eq!(SequencerModule::top_candidates(), vec![41]);
+
+		// Once 3 is registered, the scan finds enough.
+		assert_ok!(SequencerModule::register_sequencer(
+			RuntimeOrigin::signed(3),
+			empty_info(),
+			Perbill::zero()
+		));
+		assert_eq!(SequencerModule::top_candidates(), vec![3, 41]);
+	});
+}
+
+#[test]
+fn genesis_plans_and_activates_the_first_era() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(SequencerModule::current_era(), Some(0));
+		assert_eq!(active_era(), Some(0));
+		assert_eq!(SequencerModule::eras_start_session_index(0), Some(0));
+
+		// Ranked by stake.
+		assert_eq!(elected(0), vec![41, 31, 21, 11]);
+		assert_eq!(SequencerModule::eras_total_stake(0), 10_000);
+
+		let groups = SequencerModule::eras_sequencer_groups(0);
+		assert_eq!(groups.len(), 2);
+		assert!(groups.iter().all(|group| group.members.len() == 2));
+		assert!(SequencerModule::eras_standby_sequencers(0).is_empty());
+	});
+}
+
+#[test]
+fn genesis_sequencers_are_the_sequencers_of_the_first_era() {
+	ExtBuilder::default().sequencers(vec![11, 41]).build().execute_with(|| {
+		assert_eq!(SequencerModule::current_era(), Some(0));
+		assert_eq!(active_era(), Some(0));
+
+		// Ranked by stake, out of the genesis list rather than the validators.
+		assert_eq!(elected(0), vec![41, 11]);
+		assert_eq!(SequencerModule::eras_total_stake(0), 5_000);
+		assert_eq!(
+			SequencerModule::eras_exposures(0, 41),
+			Exposure { total: 4_000, own: 4_000, others: vec![] }
+		);
+		assert!(!ErasExposures::<Test>::contains_key(0, 31));
+		assert_eq!(ErasExposures::<Test>::iter_prefix(0).count(), 2);
+
+		// A single group of 2 out of the 2 genesis sequencers.
+		let groups = SequencerModule::eras_sequencer_groups(0);
+		assert_eq!(groups.len(), 1);
+		let mut members = groups[0].members.clone().into_inner();
+		members.sort();
+		assert_eq!(members, vec![11, 41]);
+		assert_eq!(groups[0].total_stake, 5_000);
+		assert!(SequencerModule::eras_standby_sequencers(0).is_empty());
+	});
+}
+
+#[test]
+fn era_is_planned_a_session_ahead_and_activated() {
+	new_test_ext().execute_with(|| {
+		// Era 0 is 3 sessions long, era 1 is planned when session 3 is.
+		run_to_block(5);
+		assert_eq!(SequencerModule::current_era(), Some(0));
+
+		run_to_block(6);
+		assert_eq!(Session::current_index(), 2);
+		assert_eq!(SequencerModule::current_era(), Some(1));
+		assert_eq!(SequencerModule::eras_start_session_index(1), Some(3));
+		assert_eq!(active_era(), Some(0));
+		let events = sequencer_events();
+		assert!(events.contains(&Event::EraPlanned { era: 1, start_session: 3 }));
+		assert!(events.contains(&Event::SequencersElected {
+			era: 1,
+			count: 4,
+			total_stake: 10_000
+		}));
+		assert!(events.contains(&Event::GroupsFormed { era: 1, groups: 2, standby: 0 }));
+
+		// Session 3 starts era 1.
+		run_to_block(9);
+		assert_eq!(Session::current_index(), 3);
+		assert_eq!(active_era(), Some(1));
+		assert_eq!(elected(1), vec![41, 31, 21, 11]);
+		assert_eq!(sequencer_events(), vec![Event::EraPaid { era: 0, reward: ERA_REWARD }]);
+
+		advance_era();
+		assert_eq!(active_era(), Some(2));
+		assert_eq!(Session::current_index(), 6);
+		assert_eq!(SequencerModule::eras_start_session_index(2), Some(6));
+	});
+}
+
+#[test]
+fn era_start_and_duration_are_recorded() {
+	new_test_ext().execute_with(|| {
+		// The start is recorded when the first block of the era is finalized.
+		assert_eq!(SequencerModule::eras_start_timestamp(0), None);
+		run_to_block(2);
+		assert_eq!(SequencerModule::eras_start_timestamp(0), Some(INIT_TIMESTAMP));
+		assert_eq!(SequencerModule::eras_duration(0), None);
+		assert_eq!(
+			SequencerModule::api_era_timing(0),
+			Some(EraTiming { start: INIT_TIMESTAMP, duration: None })
+		);
+
+		// Era 1 starts at block 9.
+		run_to_block(10);
+		let start = INIT_TIMESTAMP + 8 * BLOCK_TIME;
+		assert_eq!(SequencerModule::eras_duration(0), Some(8 * BLOCK_TIME));
+		assert_eq!(SequencerModule::eras_start_timestamp(1), Some(start));
+		assert!(sequencer_events().contains(&Event::EraStarted { era: 1, timestamp: start }));
+		assert_eq!(
+			SequencerModule::api_era_timing(0),
+			Some(EraTiming { start: INIT_TIMESTAMP, duration: Some(8 * BLOCK_TIME) })
+		);
+		assert_eq!(SequencerModule::api_era_timing(2), None);
+	});
+}
+
+#[test]
+fn config_applies_from_the_next_planned_era() {
+	new_test_ext().execute_with(|| {
+		let config = EraConfig { sessions_per_era: 2, history_depth: 4, min_sequencer_count: 1 };
+		assert_ok!(SequencerModule::set_config(RuntimeOrigin::root(), config));
+		assert_eq!(SequencerModule::pending_config(), Some(config));
+		assert_eq!(SequencerModule::next_config(), config);
+		assert_eq!(SequencerModule::current_config().sessions_per_era, 3);
+
+		// Era 0 keeps its 3 sessions.
+		run_to_block(6);
+		assert_eq!(SequencerModule::eras_start_session_index(1), Some(3));
+		assert_eq!(SequencerModule::current_config(), config);
+		assert_eq!(SequencerModule::pending_config(), None);
+		assert!(sequencer_events().contains(&Event::ConfigApplied { era: 1, config }));
+
+		// Era 1 only lasts 2 sessions.
+		run_to_block(12);
+		assert_eq!(SequencerModule::eras_start_session_index(2), Some(5));
+	});
+}
+
+#[test]
+fn history_depth_is_read_from_the_config() {
+	new_test_ext().execute_with(|| {
+		let config = EraConfig { sessions_per_era: 3, history_depth: 2, min_sequencer_count: 1 };
+		assert_ok!(SequencerModule::set_config(RuntimeOrigin::root(), config));
+
+		// Era 4 starts at block 36, and era 1 is pruned right after.
+		run_to_block(37);
+		assert_eq!(active_era(), Some(4));
+		assert_eq!(SequencerModule::current_config().history_depth, 2);
+
+		// Era 1 would still be in history with the default depth of 4.
+		assert_noop!(
+			SequencerModule::payout_era(RuntimeOrigin::signed(1), 1, 41),
+			Error::<Test>::InvalidEra
+		);
+		assert!(SequencerModule::eras_sequencers(1).is_empty());
+		assert!(!SequencerModule::eras_sequencers(2).is_empty());
+	});
+}
+
+#[test]
+fn era_which_just_ended_stays_in_history_with_a_depth_of_one() {
+	new_test_ext().execute_with(|| {
+		let config = EraConfig { sessions_per_era: 3, history_depth: 1, min_sequencer_count: 1 };
+		assert_ok!(SequencerModule::set_config(RuntimeOrigin::root(), config));
+
+		// Era 3 is planned at block 24 while era 2 is still active.
+		run_to_block(25);
+		assert_eq!(SequencerModule::current_era(), Some(3));
+		assert_eq!(active_era(), Some(2));
+
+		// Era 0 is out of history and pruned.
+		assert_noop!(
+			SequencerModule::payout_era(RuntimeOrigin::signed(1), 0, 41),
+			Error::<Test>::InvalidEra
+		);
+		assert!(SequencerModule::eras_sequencers(0).is_empty());
+
+		// The era which just ended and the active era are not.
+		assert_ok!(SequencerModule::payout_era(RuntimeOrigin::signed(1), 1, 41));
+		let fraction = Perbill::from_percent(10);
+		assert_ok!(SequencerModule::report_offence(RuntimeOrigin::root(), 1, 41, fraction));
+		assert_ok!(SequencerModule::report_offence(RuntimeOrigin::root(), 2, 31, fraction));
+		for era in 1..=3 {
+			assert_eq!(elected(era), vec![41, 31, 21, 11]);
+		}
+	});
+}
+
+#[test]
+fn era_is_not_planned_when_its_groups_can_not_be_stored() {
+	new_test_ext().execute_with(|| {
+		// More groups than `MaxSequencers`.
+		ExtraGroups::set(16);
+		run_to_block(7);
+		assert_eq!(SequencerModule::current_era(), Some(0));
+		assert_eq!(SequencerModule::eras_start_session_index(1), None);
+		assert!(SequencerModule::eras_sequencers(1).is_empty());
+		assert!(SequencerModule::eras_sequencer_groups(1).is_empty());
+		assert!(sequencer_events().contains(&Event::GroupingFailed {
+			session: 3,
+			error: Error::<Test>::TooManyGroups.into(),
+		}));
+
+		// Retried at the next session.
+		ExtraGroups::set(0);
+		run_to_block(10);
+		assert_eq!(SequencerModule::current_era(), Some(1));
+		assert_eq!(SequencerModule::eras_start_session_index(1), Some(4));
+		assert_eq!(SequencerModule::eras_sequencer_groups(1).len(), 2);
+	});
+}
+
+#[test]
+fn invalid_config_is_rejected() {
+	new_test_ext().execute_with(|| {
+		let config = EraConfig { sessions_per_era: 3, history_depth: 4, min_sequencer_count: 1 };
+		assert_noop!(
+			SequencerModule::set_config(
+				RuntimeOrigin::root(),
+				EraConfig { sessions_per_era: 0, ..config }
+			),
+			Error::<Test>::ZeroSessionsPerEra
+		);
+		assert_noop!(
+			SequencerModule::set_config(
+				RuntimeOrigin::root(),
+				EraConfig { history_depth: 0, ..config }
+			),
+			Error::<Test>::ZeroHistoryDepth
+		);
+		assert_noop!(
+			SequencerModule::set_config(
+				RuntimeOrigin::root(),
+				EraConfig { min_sequencer_count: 17, ..config }
+			),
+			Error::<Test>::MinSequencerCountTooHigh
+		);
+		assert_noop!(
+			SequencerModule::set_config(RuntimeOrigin::signed(1), config),
+			DispatchError::BadOrigin
+		);
+	});
+}
+
+#[test]
+fn force_new_era_plans_an_era_at_the_next_session() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(SequencerModule::force_new_era(RuntimeOrigin::root()));
+		assert_eq!(SequencerModule::force_era(), Forcing::ForceNew);
+
+		// Session 2 is the next one planned.
+		advance_session();
+		assert_eq!(SequencerModule::current_era(), Some(1));
+		assert_eq!(SequencerModule::eras_start_session_index(1), Some(2));
+		assert_eq!(SequencerModule::force_era(), Forcing::NotForcing);
+		assert!(sequencer_events().contains(&Event::ForcingChanged { mode: Forcing::NotForcing }));
+
+		advance_session();
+		assert_eq!(active_era(), Some(1));
+
+		// Back to normal, era 1 lasts 3 sessions.
+		advance_era();
+		assert_eq!(SequencerModule::eras_start_session_index(2), Some(5));
+	});
+}
+
+#[test]
+fn force_no_eras_stops_planning_eras() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(SequencerModule::force_no_eras(RuntimeOrigin::root()));
+
+		run_to_block(10 * BLOCKS_PER_SESSION);
+		assert_eq!(SequencerModule::current_era(), Some(0));
+		assert_eq!(active_era(), Some(0));
+
+		// Planning resumes once unforced.
+		assert_ok!(SequencerModule::force_new_era(RuntimeOrigin::root()));
+		advance_session();
+		assert_eq!(SequencerModule::current_era(), Some(1));
+	});
+}
+
+#[test]
+fn force_new_era_always_plans_an_era_every_session() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(SequencerModule::force_new_era_always(RuntimeOrigin::root()));
+
+		run_to_block(4 * BLOCKS_PER_SESSION);
+		assert_eq!(SequencerModule::current_era(), Some(4));
+		assert_eq!(active_era(), Some(3));
+		for era in 1..=4 {
+			assert_eq!(SequencerModule::eras_start_session_index(era), Some(era + 1));
+		}
+		assert_eq!(SequencerModule::force_era(), Forcing::ForceAlways);
+	});
+}
+
+#[test]
+fn forcing_requires_the_admin_origin() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			SequencerModule::force_new_era(RuntimeOrigin::signed(1)),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			SequencerModule::force_no_eras(RuntimeOrigin::signed(1)),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			SequencerModule::force_new_era_always(RuntimeOrigin::signed(1)),
+			DispatchError::BadOrigin
+		);
+	});
+}
+
+#[test]
+fn group_layout_is_set_by_the_admin_origin() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			SequencerModule::set_sequencers_per_group(RuntimeOrigin::signed(1), 3),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			SequencerModule::set_group_count(RuntimeOrigin::signed(1), 3),
+			DispatchError::BadOrigin
+		);
+
+		assert_ok!(SequencerModule::set_sequencers_per_group(RuntimeOrigin::root(), 1));
+		assert_ok!(SequencerModule::set_group_count(RuntimeOrigin::root(), 3));
+		run_to_block(6);
+		let groups = SequencerModule::eras_sequencer_groups(1);
+		assert_eq!(groups.len(), 3);
+		assert!(groups.iter().all(|group| group.members.len() == 1));
+		// Only as many seats as the groups need.
+		assert_eq!(elected(1), vec![41, 31, 21]);
+	});
+}
+
+#[test]
+fn no_era_at_genesis_when_eras_are_forced_off() {
+	ExtBuilder::default().force_era(Forcing::ForceNone).build().execute_with(|| {
+		assert_eq!(SequencerModule::current_era(), None);
+		assert_eq!(active_era(), None);
+
+		advance_session();
+		assert_eq!(SequencerModule::current_era(), None);
+
+		// The first era is planned at the next session once forced.
+		assert_ok!(SequencerModule::force_new_era(RuntimeOrigin::root()));
+		advance_session();
+		assert_eq!(SequencerModule::current_era(), Some(0));
+		assert_eq!(SequencerModule::eras_start_session_index(0), Some(3));
+		advance_session();
+		assert_eq!(active_era(), Some(0));
+	});
+}
+
+#[test]
+fn first_era_waits_for_candidates() {
+	ExtBuilder::default().stakers(vec![]).build().execute_with(|| {
+		assert_eq!(SequencerModule::current_era(), None);
+
+		advance_session();
+		assert_eq!(SequencerModule::current_era(), None);
+		assert!(sequencer_events()
+			.contains(&Event::ElectionFailed { session: 2, error: ElectionError::NoCandidates }));
+
+		bond_and_register(11, 1_000);
+		advance_session();
+		assert_eq!(SequencerModule::current_era(), Some(0));
+		assert_eq!(SequencerModule::eras_start_session_index(0), Some(3));
+		assert_eq!(elected(0), vec![11]);
+	});
+}
+
+#[test]
+fn failed_election_keeps_the_era_and_is_retried() {
+	new_test_ext().execute_with(|| {
+		// Only 4 candidates for at least 5 sequencers.
+		let config = EraConfig { sessions_per_era: 3, history_depth: 4, min_sequencer_count: 5 };
+		assert_ok!(SequencerModule::set_config(RuntimeOrigin::root(), config));
+
+		run_to_block(6);
+		assert_eq!(SequencerModule::current_era(), Some(0));
+		assert_eq!(SequencerModule::pending_config(), Some(config));
+		assert!(sequencer_events().contains(&Event::ElectionFailed {
+			session: 3,
+			error: ElectionError::NotEnoughWinners,
+		}));
+
+		// Era 0 goes on.
+		run_to_block(9);
+		assert_eq!(active_era(), Some(0));
+
+		// A fifth candidate joins before the retry.
+		bond_and_register(51, 5_000);
+		Validators::set(vec![11, 21, 31, 41, 51]);
+		run_to_block(12);
+		assert_eq!(SequencerModule::current_era(), Some(1));
+		assert_eq!(SequencerModule::eras_start_session_index(1), Some(5));
+		assert_eq!(SequencerModule::current_config(), config);
+		assert_eq!(elected(1), vec![51, 41, 31, 21, 11]);
+	});
+}
+
+#[test]
+fn only_registered_validators_are_elected() {
+	new_test_ext().execute_with(|| {
+		// 61 validates but is not registered, 51 is registered but does not validate.
+		assert_ok!(SequencerModule::bond(RuntimeOrigin::signed(61), 6_000));
+		bond_and_register(51, 5_000);
+		Validators::set(vec![11, 21, 31, 41, 61]);
+
+		advance_era();
+		assert_eq!(elected(1), vec![41, 31, 21, 11]);
+
+		assert_ok!(SequencerModule::register_sequencer(
+			RuntimeOrigin::signed(61),
+			empty_info(),
+			Perbill::zero()
+		));
+		advance_era();
+		assert_eq!(elected(2), vec![61, 41, 31, 21]);
+	});
+}
+
+#[test]
+fn elected_sequencers_are_capped() {
+	let stakers = (1..=20).map(|i| (100 + i, 100 * i as u128)).collect::<Vec<_>>();
+	let validators = stakers.iter().map(|(who, _)| *who).collect();
+	ExtBuilder::default()
+		.stakers(stakers)
+		.validators(validators)
+		.groups(2, 10)
+		.build()
+		.execute_with(|| {
+			let max_sequencers = <Test as crate::Config>::MaxSequencers::get();
+			let sequencers = elected(0);
+			assert_eq!(sequencers.len() as u32, max_sequencers);
+			assert_eq!(sequencers.first(), Some(&120));
+			assert_eq!(sequencers.last(), Some(&105));
+			assert_eq!(ErasExposures::<Test>::iter_prefix(0).count() as u32, max_sequencers);
+
+			// As many groups as the elected sequencers fill.
+			assert_eq!(SequencerModule::eras_sequencer_groups(0).len(), 8);
+			assert!(SequencerModule::eras_standby_sequencers(0).is_empty());
+		});
+}
+
+#[test]
+fn sequencers_left_out_of_the_groups_are_standby() {
+	ExtBuilder::default()
+		.stakers(vec![(11, 1_000), (21, 2_000), (31, 3_000), (41, 4_000), (51, 5_000)])
+		.validators(vec![11, 21, 31, 41, 51])
+		.era_config(EraConfig { sessions_per_era: 3, history_depth: 4, min_sequencer_count: 5 })
+		.build()
+		.execute_with(|| {
+			assert_eq!(elected(0), vec![51, 41, 31, 21, 11]);
+
+			let groups = SequencerModule::eras_sequencer_groups(0);
+			assert_eq!(groups.len(), 2);
+			assert_eq!(groups.iter().map(|group| group.total_stake).sum::<u128>(), 14_000);
+			assert_eq!(SequencerModule::eras_standby_sequencers(0).into_inner(), vec![11]);
+			assert_eq!(SequencerModule::api_group_of(&11, 0), None);
+		});
+}
+
+#[test]
+fn authority_mode_hands_the_sequencers_to_the_session() {
+	ExtBuilder::default()
+		.session_mode(crate::SessionMode::Authority)
+		.build()
+		.execute_with(|| {
+			assert_eq!(Session::validators(), vec![41, 31, 21, 11]);
+
+			bond_and_register(51, 5_000);
+			advance_era();
+			assert_eq!(active_era(), Some(1));
+			assert_eq!(Session::validators(), vec![51, 41, 31, 21]);
+		});
+}
+
+#[test]
+fn unbonded_funds_are_withdrawn_after_the_bonding_duration() {
+	new_test_ext().execute_with(|| {
+		let restaking = RuntimeHoldReason::from(HoldReason::Restaking);
+
+		assert_ok!(SequencerModule::unbond(RuntimeOrigin::signed(11), 400));
+		assert!(sequencer_events().contains(&Event::Unbonded { who: 11, amount: 400, era: 3 }));
+		assert_eq!(SequencerModule::restake_data(11), 600);
+
+		// Still held until era 3 is planned.
+		advance_era();
+		assert_ok!(SequencerModule::withdraw_unbonded(RuntimeOrigin::signed(11)));
+		assert_eq!(Balances::balance_on_hold(&restaking, &11), 1_000);
+
+		while SequencerModule::current_era() < Some(3) {
+			advance_session();
+		}
+		assert_ok!(SequencerModule::withdraw_unbonded(RuntimeOrigin::signed(11)));
+		assert_eq!(Balances::balance_on_hold(&restaking, &11), 600);
+		assert!(sequencer_events().contains(&Event::Withdrawn { who: 11, amount: 400 }));
+		assert!(SequencerModule::unlocking(11).is_empty());
+	});
+}
+
+#[test]
+fn eras_out_of_history_are_pruned() {
+	new_test_ext().execute_with(|| {
+		for _ in 0..6 {
+			advance_era();
+		}
+		run_to_block(System::block_number() + 1);
+		// With a history depth of 4, the active era 6 and the 4 eras before it are kept.
+		assert_eq!(SequencerModule::current_era(), Some(6));
+		assert_eq!(SequencerModule::history_pruning().era, 2);
+
+		for era in 0..2 {
+			assert_eq!(SequencerModule::eras_start_session_index(era), None);
+			assert!(SequencerModule::eras_sequencers(era).is_empty());
+			assert_eq!(SequencerModule::eras_start_timestamp(era), None);
+			assert_eq!(SequencerModule::eras_duration(era), None);
+			assert_eq!(ErasExposures::<Test>::iter_prefix(era).count(), 0);
+			assert_eq!(ErasCommissions::<Test>::iter_prefix(era).count(), 0);
+		}
+		for era in 2..=6 {
+			assert!(SequencerModule::eras_start_session_index(era).is_some());
+			assert_eq!(elected(era), vec![41, 31, 21, 11]);
+			assert_eq!(SequencerModule::eras_exposures(era, 41).total, 4_000);
+		}
+	});
+}
+
+#[test]
+fn pruning_resumes_from_its_cursor() {
+	let mut ext = new_test_ext();
+	ext.execute_with(|| {
+		let exposure = SequencerModule::eras_exposures(0, 11);
+		for who in 100..106 {
+			ErasExposures::<Test>::insert(0, who, exposure.clone());
+		}
+		crate::CurrentEra::<Test>::put(10);
+		crate::ActiveEra::<Test>::put(ActiveEraInfo { index: 10, start: None });
+	});
+	// The keys are only cleared a few at a time out of the backend.
+	ext.commit_all().unwrap();
+
+	ext.execute_with(|| {
+		let db_weight = <Test as frame_system::Config>::DbWeight::get();
+		let budget = db_weight.reads_writes(3, 1) +
+			db_weight.writes(10) +
+			SequencerModule::pruned_key_weight() * 3;
+		assert_eq!(SequencerModule::prune_history(budget), budget);
+
+		let progress = SequencerModule::history_pruning();
+		assert_eq!(progress.era, 0);
+		assert_eq!(progress.stage, PruningStage::Exposures);
+		assert!(progress.cursor.is_some());
+		assert!(SequencerModule::eras_sequencers(0).is_empty());
+		assert_eq!(ErasExposures::<Test>::iter_prefix(0).count(), 7);
+
+		SequencerModule::prune_history(Weight::MAX);
+		let progress = SequencerModule::history_pruning();
+		assert_eq!(progress.era, 6);
+		assert_eq!(progress.stage, PruningStage::Values);
+		assert_eq!(progress.cursor, None);
+		assert_eq!(ErasExposures::<Test>::iter_prefix(0).count(), 0);
+		assert_eq!(ErasCommissions::<Test>::iter_prefix(0).count(), 0);
+	});
+}
+
+#[test]
+fn pruning_needs_enough_weight() {
+	new_test_ext().execute_with(|| {
+		crate::CurrentEra::<Test>::put(10);
+		crate::ActiveEra::<Test>::put(ActiveEraInfo { index: 10, start: None });
+		assert_eq!(SequencerModule::prune_history(Weight::zero()), Weight::zero());
+		assert_eq!(SequencerModule::history_pruning().era, 0);
+		assert!(!SequencerModule::eras_sequencers(0).is_empty());
+	});
+}
+
+#[test]
+fn snake_draft_balances_the_groups() {
+	let ranked = (1..=8).rev().map(|stake| (stake, stake as u128 * 100)).collect::<Vec<_>>();
+	let groups = SnakeDraft::assign(ranked, 2, 4);
+	assert_eq!(
+		groups,
+		vec![
+			vec![(8, 800), (5, 500), (4, 400), (1, 100)],
+			vec![(7, 700), (6, 600), (3, 300), (2, 200)],
+		]
+	);
+	assert_eq!(group_totals(&groups), vec![1_800, 1_800]);
+}
+
+#[test]
+fn snake_draft_keeps_the_given_order_within_a_round() {
+	// The order given, e.g. shuffled, decides the picks within every round of two.
+	let shuffled =
+		vec![(3, 300), (8, 800), (1, 100), (6, 600), (4, 400), (7, 700), (2, 200), (5, 500)];
+	let groups = SnakeDraft::assign(shuffled, 2, 4);
+	assert_eq!(
+		groups,
+		vec![
+			vec![(8, 800), (5, 500), (3, 300), (2, 200)],
+			vec![(7, 700), (6, 600), (4, 400), (1, 100)],
+		]
+	);
+
+	// Every group still gets one sequencer of every round.
+	for group in &groups {
+		let mut rounds = group.iter().map(|(who, _)| (8 - who) / 2).collect::<Vec<_>>();
+		rounds.sort();
+		assert_eq!(rounds, vec![0, 1, 2, 3]);
+	}
+	assert_eq!(group_totals(&groups), vec![1_800, 1_800]);
+}
+
+#[test]
+fn greedy_balance_balances_the_groups() {
+	let ranked = (1..=8).rev().map(|stake| (stake, stake as u128 * 100)).collect::<Vec<_>>();
+	let groups = GreedyBalance::assign(ranked, 2, 4);
+	assert!(groups.iter().all(|group| group.len() == 4));
+	assert_eq!(group_totals(&groups), vec![1_800, 1_800]);
+
+	// In any order, the spread stays below the largest stake.
+	let shuffled =
+		vec![(1, 100), (2, 200), (8, 800), (3, 300), (7, 700), (4, 400), (6, 600), (5, 500)];
+	let groups = GreedyBalance::assign(shuffled, 2, 4);
+	assert!(groups.iter().all(|group| group.len() == 4));
+	let totals = group_totals(&groups);
+	assert_eq!(totals.iter().sum::<u128>(), 3_600);
+	assert!(totals.iter().max().unwrap() - totals.iter().min().unwrap() <= 800);
+}
+
+#[test]
+fn migration_to_v1_removes_the_unheld_restake_data() {
+	ExtBuilder::default().stakers(vec![]).build().execute_with(|| {
+		// Set by the former `deposit` call, nothing held.
+		StorageVersion::new(0).put::<SequencerModule>();
+		crate::RestakeData::<Test>::insert(1, 500);
+		crate::RestakeData::<Test>::insert(2, 700);
+
+		crate::migrations::v1::MigrateToV1::<Test>::on_runtime_upgrade();
+		assert_eq!(crate::RestakeData::<Test>::iter().count(), 0);
+		assert_eq!(SequencerModule::on_chain_storage_version(), 1);
+
+		// Only migrates once.
+		crate::RestakeData::<Test>::insert(1, 500);
+		crate::migrations::v1::MigrateToV1::<Test>::on_runtime_upgrade();
+		assert_eq!(SequencerModule::restake_data(1), 500);
+	});
+}
+
+#[test]
+fn migration_to_v2_ranks_the_sequencers() {
+	ExtBuilder::default().stakers(vec![]).build().execute_with(|| {
+		StorageVersion::new(1).put::<SequencerModule>();
+		// The sequencers as they were stored up to v1.
+		let old: Vec<(u64, u128)> = (1..=20).map(|who| (who, 100 * who as u128)).collect();
+		frame_support::storage::unhashed::put(
+			&crate::ErasSequencers::<Test>::hashed_key_for(3),
+			&old,
+		);
+
+		crate::migrations::v2::MigrateToV2::<Test>::on_runtime_upgrade();
+		assert_eq!(SequencerModule::on_chain_storage_version(), 2);
+
+		// Ranked by stake, bounded by `MaxSequencers`.
+		let sequencers = SequencerModule::eras_sequencers(3);
+		assert_eq!(sequencers.len(), 16);
+		assert_eq!(elected(3), (5..=20).rev().collect::<Vec<u64>>());
+		assert!(sequencers.iter().enumerate().all(|(rank, s)| s.rank == rank as u32));
+		assert_eq!(
+			SequencerModule::eras_total_stake(3),
+			(5..=20).map(|who| 100 * who).sum::<u128>()
+		);
+	});
+}
+
+#[test]
+fn migration_to_v3_sorts_the_stakers() {
+	ExtBuilder::default().stakers(vec![]).build().execute_with(|| {
+		StorageVersion::new(2).put::<SequencerModule>();
+		// The bonds as they were stored up to v2, without their aggregates, each in its own bag.
+		for who in 1..=5u64 {
+			crate::RestakeData::<Test>::insert(who, 10 * who as u128);
+		}
+		assert_eq!(SequencerModule::restaker_count(), 0);
+
+		crate::migrations::v3::MigrateToV3::<Test>::on_runtime_upgrade();
+		assert_eq!(SequencerModule::on_chain_storage_version(), 3);
+		assert_eq!(SequencerModule::total_restake(), 150);
+		assert_eq!(SequencerModule::restaker_count(), 5);
+		assert_eq!(
+			<StakerList as SortedListProvider<u64>>::iter().collect::<Vec<_>>(),
+			vec![5, 4, 3, 2, 1]
+		);
+		assert_stakers_in_sync();
+	});
+}
+
+#[test]
+fn reported_offences_are_slashed_after_the_defer_duration() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(SequencerModule::delegate(RuntimeOrigin::signed(1), 41, 500));
+		advance_era();
+		assert_eq!(SequencerModule::eras_exposures(1, 41).total, 4_500);
+
+		assert_ok!(SequencerModule::report_offence(
+			RuntimeOrigin::root(),
+			1,
+			41,
+			Perbill::from_percent(10)
+		));
+		// Deferred by 2 eras from the current era.
+		assert!(sequencer_events().contains(&Event::SlashReported {
+			sequencer: 41,
+			era: 1,
+			fraction: Perbill::from_percent(10),
+			amount: 450,
+			apply_era: 3,
+		}));
+		assert_eq!(SequencerModule::unapplied_slashes(3).len(), 1);
+
+		advance_era();
+		assert_eq!(SequencerModule::restake_data(41), 4_000);
+
+		// Applied when era 3 starts.
+		advance_era();
+		assert_eq!(active_era(), Some(3));
+		assert!(SequencerModule::unapplied_slashes(3).is_empty());
+		assert_eq!(SequencerModule::restake_data(41), 3_600);
+		assert_eq!(SequencerModule::delegations(41, 1), 450);
+		assert_eq!(SequencerModule::eras_slashes(1, 41), 400);
+		assert_eq!(SequencerModule::eras_slashes(1, 1), 50);
+		assert_eq!(Balances::total_balance(&41), ENDOWMENT - 400);
+		assert_eq!(Balances::total_balance(&1), ENDOWMENT - 50);
+		let events = sequencer_events();
+		assert!(events.contains(&Event::Slashed { who: 41, sequencer: 41, era: 1, amount: 400 }));
+		assert!(events.contains(&Event::Slashed { who: 1, sequencer: 41, era: 1, amount: 50 }));
+	});
+}
+
+#[test]
+fn offences_are_only_reported_for_elected_sequencers_in_history() {
+	new_test_ext().execute_with(|| {
+		let fraction = Perbill::from_percent(10);
+		assert_noop!(
+			SequencerModule::report_offence(RuntimeOrigin::signed(1), 0, 41, fraction),
+			DispatchError::BadOrigin
+		);
+		// Not started yet.
+		assert_noop!(
+			SequencerModule::report_offence(RuntimeOrigin::root(), 1, 41, fraction),
+			Error::<Test>::InvalidEra
+		);
+		assert_noop!(
+			SequencerModule::report_offence(RuntimeOrigin::root(), 0, 1, fraction),
+			Error::<Test>::NotElected
+		);
+
+		// The active era 5 and the 4 eras before it are in history, like for the pruning.
+		for _ in 0..5 {
+			advance_era();
+		}
+		assert_eq!(SequencerModule::current_era(), Some(5));
+		assert_noop!(
+			SequencerModule::report_offence(RuntimeOrigin::root(), 0, 41, fraction),
+			Error::<Test>::InvalidEra
+		);
+		assert_ok!(SequencerModule::report_offence(RuntimeOrigin::root(), 1, 41, fraction));
+	});
+}
+
+#[test]
+fn sequencer_is_slashed_once_per_era_for_the_highest_fraction() {
+	new_test_ext().execute_with(|| {
+		let report = |fraction| {
+			SequencerModule::report_offence(
+				RuntimeOrigin::root(),
+				0,
+				41,
+				Perbill::from_percent(fraction),
+			)
+		};
+		assert_ok!(report(10));
+		assert_ok!(report(10));
+		assert_ok!(report(5));
+		assert_eq!(SequencerModule::unapplied_slashes(2).len(), 1);
+		assert_eq!(SequencerModule::eras_slash_fractions(0, 41), Perbill::from_percent(10));
+
+		// Only the difference to the 10% already reported.
+		assert_ok!(report(30));
+		let slashes = SequencerModule::unapplied_slashes(2);
+		assert_eq!(slashes.iter().map(|slash| slash.own).collect::<Vec<_>>(), vec![400, 800]);
+
+		advance_era();
+		advance_era();
+		assert_eq!(SequencerModule::restake_data(41), 2_800);
+		assert_eq!(SequencerModule::eras_slashes(0, 41), 1_200);
+	});
+}
+
+#[test]
+fn deferred_slashes_can_be_cancelled() {
+	new_test_ext().execute_with(|| {
+		let fraction = Perbill::from_percent(10);
+		assert_ok!(SequencerModule::report_offence(RuntimeOrigin::root(), 0, 41, fraction));
+		assert_ok!(SequencerModule::report_offence(RuntimeOrigin::root(), 0, 31, fraction));
+		assert_eq!(SequencerModule::unapplied_slashes(2).len(), 2);
+
+		assert_noop!(
+			SequencerModule::cancel_deferred_slash(RuntimeOrigin::signed(1), 2, vec![0]),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			SequencerModule::cancel_deferred_slash(RuntimeOrigin::root(), 2, vec![]),
+			Error::<Test>::EmptyTargets
+		);
+		assert_noop!(
+			SequencerModule::cancel_deferred_slash(RuntimeOrigin::root(), 2, vec![1, 0]),
+			Error::<Test>::NotSortedAndUnique
+		);
+		assert_noop!(
+			SequencerModule::cancel_deferred_slash(RuntimeOrigin::root(), 2, vec![2]),
+			Error::<Test>::InvalidSlashIndex
+		);
+
+		assert_ok!(SequencerModule::cancel_deferred_slash(RuntimeOrigin::root(), 2, vec![0]));
+		assert!(sequencer_events().contains(&Event::SlashCancelled {
+			sequencer: 41,
+			era: 0,
+			apply_era: 2
+		}));
+
+		advance_era();
+		advance_era();
+		assert_eq!(SequencerModule::restake_data(41), 4_000);
+		assert_eq!(SequencerModule::restake_data(31), 2_700);
+	});
+}
+
+#[test]
+fn slashes_reach_the_unlocking_funds() {
+	new_test_ext().execute_with(|| {
+		let restaking = RuntimeHoldReason::from(HoldReason::Restaking);
+		assert_ok!(SequencerModule::unbond(RuntimeOrigin::signed(41), 3_900));
+		assert_ok!(SequencerModule::report_offence(
+			RuntimeOrigin::root(),
+			0,
+			41,
+			Perbill::from_percent(50)
+		));
+
+		advance_era();
+		advance_era();
+		// The 100 still bonded first, then the unlocking chunk.
+		assert_eq!(SequencerModule::restake_data(41), 0);
+		let chunks = SequencerModule::unlocking(41);
+		assert_eq!(chunks.len(), 1);
+		assert_eq!((chunks[0].value, chunks[0].era), (2_000, 3));
+		assert_eq!(Balances::balance_on_hold(&restaking, &41), 2_000);
+		assert_eq!(SequencerModule::eras_slashes(0, 41), 2_000);
+	});
+}
 
 #[test]
-fn it_works_for_default_value() {
+fn offences_of_the_offences_machinery_are_reported() {
 	new_test_ext().execute_with(|| {
-		// Dispatch a signed extrinsic.
-		assert_ok!(SequencerModule::do_something(RuntimeOrigin::signed(1), 42));
-		// Read pallet storage and assert an expected result.
-		assert_eq!(SequencerModule::something(), Some(42));
+		let offence = |who: u64| OffenceDetails { offender: (who, ()), reporters: vec![] };
+		<SequencerModule as OnOffenceHandler<u64, (u64, ()), Weight>>::on_offence(
+			&[offence(31), offence(1)],
+			&[Perbill::from_percent(10), Perbill::from_percent(10)],
+			1,
+			DisableStrategy::Never,
+		);
+
+		// Session 1 is in era 0, only the elected sequencer is slashed.
+		let slashes = SequencerModule::unapplied_slashes(2);
+		assert_eq!(slashes.len(), 1);
+		assert_eq!((slashes[0].sequencer, slashes[0].era, slashes[0].own), (31, 0, 300));
+
+		advance_era();
+		advance_era();
+		assert_eq!(SequencerModule::restake_data(31), 2_700);
 	});
 }
 
 #[test]
-fn correct_error_for_none_value() {
+fn era_reward_is_split_by_points_and_stake() {
 	new_test_ext().execute_with(|| {
-		// Ensure the expected error is thrown when no value is present.
+		SequencerModule::reward_by_ids(vec![(41, 30), (11, 10)]);
+		advance_era();
+		assert_eq!(SequencerModule::eras_sequencer_reward(0), Some(ERA_REWARD));
+
+		// Half of the reward by points, out of 40, half by stake, out of 10_000.
+		assert_eq!(payout(0, 41, &[41]), vec![375 + 200]);
+		assert_eq!(payout(0, 11, &[11]), vec![125 + 50]);
+		assert_eq!(payout(0, 21, &[21]), vec![100]);
+		assert_eq!(payout(0, 31, &[31]), vec![150]);
+		assert_eq!(Balances::free_balance(REWARD_POT), ENDOWMENT - ERA_REWARD);
+		assert!(sequencer_events().contains(&Event::Rewarded {
+			who: 41,
+			sequencer: 41,
+			era: 0,
+			amount: 575
+		}));
+	});
+}
+
+#[test]
+fn era_reward_is_paid_once() {
+	new_test_ext().execute_with(|| {
+		// Not ended yet.
+		assert_noop!(
+			SequencerModule::payout_era(RuntimeOrigin::signed(1), 0, 41),
+			Error::<Test>::InvalidEra
+		);
+
+		advance_era();
 		assert_noop!(
-			SequencerModule::cause_error(RuntimeOrigin::signed(1)),
-			Error::<Test>::NoneValue
+			SequencerModule::payout_era(RuntimeOrigin::signed(1), 0, 1),
+			Error::<Test>::NotElected
+		);
+		assert_ok!(SequencerModule::payout_era(RuntimeOrigin::signed(1), 0, 41));
+		assert!(SequencerModule::claimed_rewards(0, 41));
+		assert_noop!(
+			SequencerModule::payout_era(RuntimeOrigin::signed(1), 0, 41),
+			Error::<Test>::AlreadyClaimed
+		);
+	});
+}
+
+#[test]
+fn era_reward_is_paid_while_in_history() {
+	new_test_ext().execute_with(|| {
+		// Era 5 starts at block 45.
+		run_to_block(45);
+		assert_eq!(active_era(), Some(5));
+
+		// Era 0 fell out of history even before it is pruned.
+		assert!(SequencerModule::eras_sequencer_reward(0).is_some());
+		assert_noop!(
+			SequencerModule::payout_era(RuntimeOrigin::signed(1), 0, 41),
+			Error::<Test>::InvalidEra
+		);
+		assert_ok!(SequencerModule::payout_era(RuntimeOrigin::signed(1), 1, 41));
+	});
+}
+
+#[test]
+fn delegations_back_the_operator() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			SequencerModule::delegate(RuntimeOrigin::signed(11), 11, 100),
+			Error::<Test>::SelfDelegation
+		);
+		assert_noop!(
+			SequencerModule::delegate(RuntimeOrigin::signed(1), 2, 100),
+			Error::<Test>::NotOperator
+		);
+		assert_noop!(
+			SequencerModule::undelegate(RuntimeOrigin::signed(1), 11, 100),
+			Error::<Test>::NotDelegated
+		);
+
+		assert_ok!(SequencerModule::delegate(RuntimeOrigin::signed(1), 11, 4_000));
+		let restaking = RuntimeHoldReason::from(HoldReason::Restaking);
+		assert_eq!(Balances::balance_on_hold(&restaking, &1), 4_000);
+		assert_eq!(SequencerModule::delegator_count(11), 1);
+		assert_eq!(SequencerModule::election_weight(&11), 5_000);
+
+		advance_era();
+		assert_eq!(elected(1), vec![11, 41, 31, 21]);
+		let exposure = SequencerModule::eras_exposures(1, 11);
+		assert_eq!((exposure.total, exposure.own), (5_000, 1_000));
+		assert_eq!(
+			exposure.others.iter().map(|other| (other.who, other.value)).collect::<Vec<_>>(),
+			vec![(1, 4_000)]
+		);
+
+		// Capped to the delegated funds, which go through the unbonding queue.
+		assert_ok!(SequencerModule::undelegate(RuntimeOrigin::signed(1), 11, 10_000));
+		assert!(sequencer_events().contains(&Event::Undelegated {
+			delegator: 1,
+			operator: 11,
+			amount: 4_000,
+			era: 4
+		}));
+		assert!(!crate::Delegations::<Test>::contains_key(11, 1));
+		assert_eq!(SequencerModule::delegator_count(11), 0);
+		assert_eq!(SequencerModule::total_delegated(11), 0);
+		assert_eq!(SequencerModule::election_weight(&11), 1_000);
+		assert_eq!(
+			SequencerModule::unlocking(1).iter().map(|chunk| chunk.value).sum::<u128>(),
+			4_000
+		);
+		assert_eq!(Balances::balance_on_hold(&restaking, &1), 4_000);
+
+		advance_era();
+		assert_eq!(elected(2), vec![41, 31, 21, 11]);
+	});
+}
+
+#[test]
+fn commission_is_taken_before_the_split_with_the_delegators() {
+	new_test_ext().execute_with(|| {
+		Validators::set(vec![11, 21, 31, 41, 61]);
+		assert_ok!(SequencerModule::bond(RuntimeOrigin::signed(61), 4_500));
+		assert_ok!(SequencerModule::register_sequencer(
+			RuntimeOrigin::signed(61),
+			empty_info(),
+			Perbill::from_percent(20)
+		));
+		assert_eq!(SequencerModule::commissions(61), Perbill::from_percent(20));
+		assert_ok!(SequencerModule::delegate(RuntimeOrigin::signed(1), 61, 4_500));
+
+		advance_era();
+		assert_eq!(elected(1), vec![61, 41, 31, 21]);
+		assert_eq!(SequencerModule::eras_commissions(1, 61), Perbill::from_percent(20));
+
+		SequencerModule::reward_by_ids(vec![(61, 10)]);
+		advance_era();
+
+		// 500 for the points and half of the stake reward, 150 of which are the commission,
+		// the rest split in half by stake.
+		assert_eq!(payout(1, 61, &[61, 1]), vec![150 + 300, 300]);
+		assert!(sequencer_events().contains(&Event::Rewarded {
+			who: 1,
+			sequencer: 61,
+			era: 1,
+			amount: 300
+		}));
+	});
+}
+
+#[test]
+fn deregistering_forgets_the_commission() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(SequencerModule::register_sequencer(
+			RuntimeOrigin::signed(1),
+			empty_info(),
+			Perbill::from_percent(20)
+		));
+		assert!(sequencer_events().contains(&Event::SequencerRegistered {
+			operator: 1,
+			deposit: SequencerModule::registration_deposit(&empty_info()),
+			commission: Perbill::from_percent(20)
+		}));
+
+		assert_ok!(SequencerModule::deregister_sequencer(RuntimeOrigin::signed(1)));
+		assert!(!crate::Commissions::<Test>::contains_key(1));
+	});
+}
+
+#[test]
+fn stake_weighted_sampling_is_reproducible() {
+	let candidates = [(11, 4_000), (21, 3_000), (31, 2_000), (41, 1_000), (51, 500), (61, 100)];
+
+	let samples = (0..20u8).map(|seed| sample(seed, &candidates, 3)).collect::<Vec<_>>();
+	for (seed, elected) in samples.iter().enumerate() {
+		assert_eq!(&sample(seed as u8, &candidates, 3), elected);
+		assert_eq!(elected.len(), 3);
+	}
+	// The seed does make a difference.
+	assert!(samples.iter().any(|elected| elected != &samples[0]));
+}
+
+#[test]
+fn stake_weighted_sampling_picks_everyone_once() {
+	let candidates = [(11, 4_000), (21, 3_000), (31, 2_000), (41, 1_000), (51, 0), (61, 0)];
+
+	for seed in 0..20u8 {
+		let elected = sample(seed, &candidates, 8);
+		assert_eq!(elected.len(), candidates.len());
+		let mut sorted = elected.clone();
+		sorted.sort();
+		sorted.dedup();
+		assert_eq!(sorted, vec![11, 21, 31, 41, 51, 61]);
+		// The unstaked candidates come last, in rank order.
+		assert_eq!(elected[4..], [51, 61]);
+	}
+}
+
+#[test]
+fn phragmen_splits_the_delegators_between_the_elected() {
+	new_test_ext().execute_with(|| {
+		let graph = StakeGraph {
+			candidates: vec![(11, 1_000), (21, 100), (31, 50)],
+			delegations: vec![(1, 11, 500), (1, 21, 500), (2, 31, 200)],
+		};
+		let exposures = phragmen::<NoBalancing>(graph, 2).unwrap();
+
+		// 31 and its delegator are left out.
+		assert_eq!(exposures.iter().map(|(who, _)| *who).collect::<Vec<_>>(), vec![11, 21]);
+		for (_, exposure) in &exposures {
+			let others = exposure.others.iter().map(|other| other.value).sum::<u128>();
+			assert_eq!(exposure.total, exposure.own + others);
+			assert!(exposure.others.iter().all(|other| other.who == 1));
+		}
+		// An operator only backs itself, the delegator backs both with all its delegations.
+		assert_eq!(exposures[0].1.own, 1_000);
+		assert_eq!(exposures[1].1.own, 100);
+		assert_eq!(exposures.iter().map(|(_, exposure)| exposure.total).sum::<u128>(), 2_100);
+	});
+}
+
+#[test]
+fn phragmen_balancing_evens_out_the_backing() {
+	new_test_ext().execute_with(|| {
+		let graph = StakeGraph {
+			candidates: vec![(11, 1_000), (21, 100)],
+			delegations: vec![(1, 11, 500), (1, 21, 500)],
+		};
+		let spread = |exposures: Vec<(u64, Exposure<u64, u128>)>| {
+			let totals = exposures.iter().map(|(_, exposure)| exposure.total).collect::<Vec<_>>();
+			totals[0].abs_diff(totals[1])
+		};
+
+		let unbalanced = spread(phragmen::<NoBalancing>(graph.clone(), 2).unwrap());
+		let balanced = phragmen::<Balanced>(graph, 2).unwrap();
+		assert_eq!(balanced.iter().map(|(_, exposure)| exposure.total).sum::<u128>(), 2_100);
+		let balanced = spread(balanced);
+		assert!(balanced <= unbalanced);
+		// 1_050 each, up to the rounding.
+		assert!(balanced <= 2);
+	});
+}
+
+#[test]
+fn phragmen_elects_within_the_candidates_and_the_bound() {
+	new_test_ext().execute_with(|| {
+		let graph = |candidates: Vec<(u64, u128)>| StakeGraph { candidates, delegations: vec![] };
+
+		assert_eq!(phragmen::<NoBalancing>(graph(vec![]), 2), Err(ElectionError::NoCandidates));
+
+		// No more than the candidates, however many seats.
+		let exposures = phragmen::<NoBalancing>(graph(vec![(11, 300), (21, 200)]), 3).unwrap();
+		assert_eq!(exposures.iter().map(|(who, _)| *who).collect::<Vec<_>>(), vec![11, 21]);
+
+		// No more than the bound of 4.
+		let candidates = (1..=5).map(|i| (10 * i, 100 * i as u128)).collect::<Vec<_>>();
+		assert_eq!(
+			phragmen::<NoBalancing>(graph(candidates), 5),
+			Err(ElectionError::TooManyWinners)
 		);
+
+		// Without the delegation graph, every candidate is backed by its own stake.
+		let elected =
+			<Phragmen<NoBalancing> as SequencerElection<u64, u128>>::elect::<ConstU32<4>>(
+				vec![(11, 300), (21, 200), (31, 100)],
+				2,
+				1,
+			)
+			.unwrap();
+		assert_eq!(elected.into_inner(), vec![(11, 300), (21, 200)]);
+	});
+}
+
+#[test]
+fn threshold_elects_the_best_ranked_within_the_seats_and_the_bound() {
+	let threshold = |candidates: Vec<(u64, u128)>, seats: u32, min_count: u32| {
+		<Threshold as SequencerElection<u64, u128>>::elect::<ConstU32<4>>(
+			candidates, seats, min_count,
+		)
+		.map(|elected| elected.into_inner())
+	};
+
+	// The average is 1_000, 41 and 51 do not pass the threshold of 666.
+	let candidates = vec![(51, 100), (11, 1_800), (41, 200), (31, 900), (21, 2_000)];
+	assert_eq!(threshold(candidates.clone(), 4, 1), Ok(vec![(21, 2_000), (11, 1_800), (31, 900)]));
+	assert_eq!(threshold(candidates.clone(), 2, 1), Ok(vec![(21, 2_000), (11, 1_800)]));
+	assert_eq!(threshold(candidates, 1, 4).map(|elected| elected.len()), Ok(4));
+
+	// All of them pass, only the bound of 4 is elected.
+	let candidates = (1..=6).map(|i| (10 * i, 1_000)).collect::<Vec<_>>();
+	assert_eq!(
+		threshold(candidates, 6, 1),
+		Ok(vec![(10, 1_000), (20, 1_000), (30, 1_000), (40, 1_000)])
+	);
+	assert_eq!(threshold(vec![], 6, 1), Err(ElectionError::NoCandidates));
+}
+
+#[test]
+fn staker_aggregates_follow_the_bonds() {
+	new_test_ext().execute_with(|| {
+		assert_stakers_in_sync();
+
+		assert_ok!(SequencerModule::bond(RuntimeOrigin::signed(1), 500));
+		assert_stakers_in_sync();
+		assert_ok!(SequencerModule::bond_extra(RuntimeOrigin::signed(1), 300));
+		assert_stakers_in_sync();
+		assert_eq!(SequencerModule::restaker_count(), 5);
+
+		// The delegations only move the operator in the list.
+		assert_ok!(SequencerModule::delegate(RuntimeOrigin::signed(2), 11, 700));
+		assert_stakers_in_sync();
+
+		assert_ok!(SequencerModule::unbond(RuntimeOrigin::signed(1), 800));
+		assert_stakers_in_sync();
+		assert_eq!(SequencerModule::restaker_count(), 4);
+		assert_ok!(SequencerModule::unbond(RuntimeOrigin::signed(41), 1_000));
+		assert_stakers_in_sync();
+		assert_eq!(SequencerModule::total_restake(), 9_000);
+
+		assert_ok!(SequencerModule::report_offence(
+			RuntimeOrigin::root(),
+			0,
+			41,
+			Perbill::from_percent(50)
+		));
+		advance_era();
+		advance_era();
+		// 2_000 out of the 3_000 left bonded.
+		assert_eq!(SequencerModule::restake_data(41), 1_000);
+		assert_stakers_in_sync();
+		assert_eq!(SequencerModule::total_restake(), 7_000);
 	});
 }
@@ -0,0 +1,309 @@
+//! Estimated weights for pallet_sequencer
+//!
+//! These values have NOT been measured. They are estimated from the storage accesses of the
+//! benchmarked paths, and must be replaced by the output of the benchmarks run on the reference
+//! hardware before the pallet is used on a live chain:
+//!
+//! ```text
+//! ./target/release/parachain-template-node benchmark pallet \
+//!     --chain dev \
+//!     --pallet pallet_sequencer \
+//!     --extrinsic '*' \
+//!     --steps 50 \
+//!     --repeat 20 \
+//!     --output pallets/sequencer/src/weights.rs
+//! ```
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::{Weight, constants::RocksDbWeight}};
+use sp_std::marker::PhantomData;
+
+/// Weight functions needed for pallet_sequencer.
+pub trait WeightInfo {
+	fn bond() -> Weight;
+	fn bond_extra() -> Weight;
+	fn unbond() -> Weight;
+	fn withdraw_unbonded() -> Weight;
+	fn set_sequencers_per_group() -> Weight;
+	fn set_group_count() -> Weight;
+	fn force_new_era() -> Weight;
+	fn force_no_eras() -> Weight;
+	fn force_new_era_always() -> Weight;
+	fn report_offence(d: u32, ) -> Weight;
+	fn cancel_deferred_slash(s: u32, ) -> Weight;
+	fn payout_era(d: u32, ) -> Weight;
+	fn delegate() -> Weight;
+	fn undelegate() -> Weight;
+	fn register_sequencer() -> Weight;
+	fn update_sequencer() -> Weight;
+	fn deregister_sequencer() -> Weight;
+	fn set_config() -> Weight;
+	fn elect_sequencers(v: u32, r: u32, ) -> Weight;
+	fn form_groups(g: u32, ) -> Weight;
+	fn set_exposures(s: u32, ) -> Weight;
+	fn top_candidates(s: u32, ) -> Weight;
+}
+
+/// Estimated weights for pallet_sequencer, not measured yet.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	fn bond() -> Weight {
+		Weight::from_parts(68_000_000, 8_877)
+			.saturating_add(T::DbWeight::get().reads(9_u64))
+			.saturating_add(T::DbWeight::get().writes(8_u64))
+	}
+	fn bond_extra() -> Weight {
+		Weight::from_parts(79_000_000, 8_877)
+			.saturating_add(T::DbWeight::get().reads(9_u64))
+			.saturating_add(T::DbWeight::get().writes(8_u64))
+	}
+	fn unbond() -> Weight {
+		Weight::from_parts(76_000_000, 8_877)
+			.saturating_add(T::DbWeight::get().reads(10_u64))
+			.saturating_add(T::DbWeight::get().writes(8_u64))
+	}
+	fn withdraw_unbonded() -> Weight {
+		Weight::from_parts(41_000_000, 4_764)
+			.saturating_add(T::DbWeight::get().reads(4_u64))
+			.saturating_add(T::DbWeight::get().writes(3_u64))
+	}
+	fn set_sequencers_per_group() -> Weight {
+		Weight::from_parts(7_000_000, 0)
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	fn set_group_count() -> Weight {
+		Weight::from_parts(7_000_000, 0)
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	fn force_new_era() -> Weight {
+		Weight::from_parts(7_000_000, 0)
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	fn force_no_eras() -> Weight {
+		Weight::from_parts(7_000_000, 0)
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	fn force_new_era_always() -> Weight {
+		Weight::from_parts(7_000_000, 0)
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// The range of component `d` is `[0, 64]`.
+	fn report_offence(d: u32, ) -> Weight {
+		Weight::from_parts(34_000_000, 5_120)
+			.saturating_add(Weight::from_parts(1_200_000, 0).saturating_mul(d.into()))
+			.saturating_add(Weight::from_parts(0, 80).saturating_mul(d.into()))
+			.saturating_add(T::DbWeight::get().reads(5_u64))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	/// The range of component `s` is `[1, 512]`.
+	fn cancel_deferred_slash(s: u32, ) -> Weight {
+		Weight::from_parts(21_000_000, 3_580)
+			.saturating_add(Weight::from_parts(420_000, 0).saturating_mul(s.into()))
+			.saturating_add(Weight::from_parts(0, 96).saturating_mul(s.into()))
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// The range of component `d` is `[0, 64]`.
+	fn payout_era(d: u32, ) -> Weight {
+		Weight::from_parts(88_000_000, 12_340)
+			.saturating_add(Weight::from_parts(27_000_000, 0).saturating_mul(d.into()))
+			.saturating_add(Weight::from_parts(0, 2_603).saturating_mul(d.into()))
+			.saturating_add(T::DbWeight::get().reads(11_u64))
+			.saturating_add(T::DbWeight::get().reads((1_u64).saturating_mul(d.into())))
+			.saturating_add(T::DbWeight::get().writes(3_u64))
+			.saturating_add(T::DbWeight::get().writes((1_u64).saturating_mul(d.into())))
+	}
+	fn delegate() -> Weight {
+		Weight::from_parts(84_000_000, 8_877)
+			.saturating_add(T::DbWeight::get().reads(11_u64))
+			.saturating_add(T::DbWeight::get().writes(9_u64))
+	}
+	fn undelegate() -> Weight {
+		Weight::from_parts(81_000_000, 8_877)
+			.saturating_add(T::DbWeight::get().reads(11_u64))
+			.saturating_add(T::DbWeight::get().writes(9_u64))
+	}
+	fn register_sequencer() -> Weight {
+		Weight::from_parts(52_000_000, 7_310)
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().writes(3_u64))
+	}
+	fn update_sequencer() -> Weight {
+		Weight::from_parts(56_000_000, 7_310)
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	fn deregister_sequencer() -> Weight {
+		Weight::from_parts(44_000_000, 7_310)
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().writes(3_u64))
+	}
+	fn set_config() -> Weight {
+		Weight::from_parts(8_000_000, 0)
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// The range of component `v` is `[1, 1000]`.
+	/// The range of component `r` is `[0, 1000]`.
+	fn elect_sequencers(v: u32, r: u32, ) -> Weight {
+		Weight::from_parts(25_000_000, 3_560)
+			.saturating_add(Weight::from_parts(96_000_000, 0).saturating_mul(v.into()))
+			.saturating_add(Weight::from_parts(48_000_000, 0).saturating_mul(r.into()))
+			.saturating_add(Weight::from_parts(0, 6_226).saturating_mul(v.into()))
+			.saturating_add(Weight::from_parts(0, 2_603).saturating_mul(r.into()))
+			.saturating_add(T::DbWeight::get().reads(5_u64))
+			.saturating_add(T::DbWeight::get().reads((3_u64).saturating_mul(v.into())))
+			.saturating_add(T::DbWeight::get().reads((1_u64).saturating_mul(r.into())))
+	}
+	/// The range of component `g` is `[1, 100]`.
+	fn form_groups(g: u32, ) -> Weight {
+		Weight::from_parts(310_000_000, 1_489)
+			.saturating_add(Weight::from_parts(4_100_000, 0).saturating_mul(g.into()))
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().writes(3_u64))
+	}
+	/// The range of component `s` is `[1, 100]`.
+	fn set_exposures(s: u32, ) -> Weight {
+		Weight::from_parts(4_000_000, 0)
+			.saturating_add(Weight::from_parts(21_000_000, 0).saturating_mul(s.into()))
+			.saturating_add(Weight::from_parts(0, 2_520).saturating_mul(s.into()))
+			.saturating_add(T::DbWeight::get().reads((1_u64).saturating_mul(s.into())))
+			.saturating_add(T::DbWeight::get().writes((2_u64).saturating_mul(s.into())))
+	}
+	/// The range of component `s` is `[1, 2000]`.
+	fn top_candidates(s: u32, ) -> Weight {
+		Weight::from_parts(6_000_000, 1_489)
+			.saturating_add(Weight::from_parts(27_000_000, 0).saturating_mul(s.into()))
+			.saturating_add(Weight::from_parts(0, 3_774).saturating_mul(s.into()))
+			.saturating_add(T::DbWeight::get().reads(3_u64))
+			.saturating_add(T::DbWeight::get().reads((3_u64).saturating_mul(s.into())))
+	}
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+	fn bond() -> Weight {
+		Weight::from_parts(68_000_000, 8_877)
+			.saturating_add(RocksDbWeight::get().reads(9_u64))
+			.saturating_add(RocksDbWeight::get().writes(8_u64))
+	}
+	fn bond_extra() -> Weight {
+		Weight::from_parts(79_000_000, 8_877)
+			.saturating_add(RocksDbWeight::get().reads(9_u64))
+			.saturating_add(RocksDbWeight::get().writes(8_u64))
+	}
+	fn unbond() -> Weight {
+		Weight::from_parts(76_000_000, 8_877)
+			.saturating_add(RocksDbWeight::get().reads(10_u64))
+			.saturating_add(RocksDbWeight::get().writes(8_u64))
+	}
+	fn withdraw_unbonded() -> Weight {
+		Weight::from_parts(41_000_000, 4_764)
+			.saturating_add(RocksDbWeight::get().reads(4_u64))
+			.saturating_add(RocksDbWeight::get().writes(3_u64))
+	}
+	fn set_sequencers_per_group() -> Weight {
+		Weight::from_parts(7_000_000, 0)
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	fn set_group_count() -> Weight {
+		Weight::from_parts(7_000_000, 0)
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	fn force_new_era() -> Weight {
+		Weight::from_parts(7_000_000, 0)
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	fn force_no_eras() -> Weight {
+		Weight::from_parts(7_000_000, 0)
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	fn force_new_era_always() -> Weight {
+		Weight::from_parts(7_000_000, 0)
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	fn report_offence(d: u32, ) -> Weight {
+		Weight::from_parts(34_000_000, 5_120)
+			.saturating_add(Weight::from_parts(1_200_000, 0).saturating_mul(d.into()))
+			.saturating_add(Weight::from_parts(0, 80).saturating_mul(d.into()))
+			.saturating_add(RocksDbWeight::get().reads(5_u64))
+			.saturating_add(RocksDbWeight::get().writes(2_u64))
+	}
+	fn cancel_deferred_slash(s: u32, ) -> Weight {
+		Weight::from_parts(21_000_000, 3_580)
+			.saturating_add(Weight::from_parts(420_000, 0).saturating_mul(s.into()))
+			.saturating_add(Weight::from_parts(0, 96).saturating_mul(s.into()))
+			.saturating_add(RocksDbWeight::get().reads(1_u64))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	fn payout_era(d: u32, ) -> Weight {
+		Weight::from_parts(88_000_000, 12_340)
+			.saturating_add(Weight::from_parts(27_000_000, 0).saturating_mul(d.into()))
+			.saturating_add(Weight::from_parts(0, 2_603).saturating_mul(d.into()))
+			.saturating_add(RocksDbWeight::get().reads(11_u64))
+			.saturating_add(RocksDbWeight::get().reads((1_u64).saturating_mul(d.into())))
+			.saturating_add(RocksDbWeight::get().writes(3_u64))
+			.saturating_add(RocksDbWeight::get().writes((1_u64).saturating_mul(d.into())))
+	}
+	fn delegate() -> Weight {
+		Weight::from_parts(84_000_000, 8_877)
+			.saturating_add(RocksDbWeight::get().reads(11_u64))
+			.saturating_add(RocksDbWeight::get().writes(9_u64))
+	}
+	fn undelegate() -> Weight {
+		Weight::from_parts(81_000_000, 8_877)
+			.saturating_add(RocksDbWeight::get().reads(11_u64))
+			.saturating_add(RocksDbWeight::get().writes(9_u64))
+	}
+	fn register_sequencer() -> Weight {
+		Weight::from_parts(52_000_000, 7_310)
+			.saturating_add(RocksDbWeight::get().reads(2_u64))
+			.saturating_add(RocksDbWeight::get().writes(3_u64))
+	}
+	fn update_sequencer() -> Weight {
+		Weight::from_parts(56_000_000, 7_310)
+			.saturating_add(RocksDbWeight::get().reads(2_u64))
+			.saturating_add(RocksDbWeight::get().writes(2_u64))
+	}
+	fn deregister_sequencer() -> Weight {
+		Weight::from_parts(44_000_000, 7_310)
+			.saturating_add(RocksDbWeight::get().reads(2_u64))
+			.saturating_add(RocksDbWeight::get().writes(3_u64))
+	}
+	fn set_config() -> Weight {
+		Weight::from_parts(8_000_000, 0)
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	fn elect_sequencers(v: u32, r: u32, ) -> Weight {
+		Weight::from_parts(25_000_000, 3_560)
+			.saturating_add(Weight::from_parts(96_000_000, 0).saturating_mul(v.into()))
+			.saturating_add(Weight::from_parts(48_000_000, 0).saturating_mul(r.into()))
+			.saturating_add(Weight::from_parts(0, 6_226).saturating_mul(v.into()))
+			.saturating_add(Weight::from_parts(0, 2_603).saturating_mul(r.into()))
+			.saturating_add(RocksDbWeight::get().reads(5_u64))
+			.saturating_add(RocksDbWeight::get().reads((3_u64).saturating_mul(v.into())))
+			.saturating_add(RocksDbWeight::get().reads((1_u64).saturating_mul(r.into())))
+	}
+	fn form_groups(g: u32, ) -> Weight {
+		Weight::from_parts(310_000_000, 1_489)
+			.saturating_add(Weight::from_parts(4_100_000, 0).saturating_mul(g.into()))
+			.saturating_add(RocksDbWeight::get().reads(2_u64))
+			.saturating_add(RocksDbWeight::get().writes(3_u64))
+	}
+	fn set_exposures(s: u32, ) -> Weight {
+		Weight::from_parts(4_000_000, 0)
+			.saturating_add(Weight::from_parts(21_000_000, 0).saturating_mul(s.into()))
+			.saturating_add(Weight::from_parts(0, 2_520).saturating_mul(s.into()))
+			.saturating_add(RocksDbWeight::get().reads((1_u64).saturating_mul(s.into())))
+			.saturating_add(RocksDbWeight::get().writes((2_u64).saturating_mul(s.into())))
+	}
+	fn top_candidates(s: u32, ) -> Weight {
+		Weight::from_parts(6_000_000, 1_489)
+			.saturating_add(Weight::from_parts(27_000_000, 0).saturating_mul(s.into()))
+			.saturating_add(Weight::from_parts(0, 3_774).saturating_mul(s.into()))
+			.saturating_add(RocksDbWeight::get().reads(3_u64))
+			.saturating_add(RocksDbWeight::get().reads((3_u64).saturating_mul(s.into())))
+	}
+}
@@ -1,13 +1,13 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 #![recursion_limit = "256"]
 
-use parity_scale_codec::{Decode, Encode, MaxEncodedLen};
+use parity_scale_codec::{Codec, Decode, Encode, MaxEncodedLen};
 
 use scale_info::TypeInfo;
 use sp_runtime::{RuntimeDebug, Saturating};
-use sp_staking::EraIndex;
+pub use sp_staking::{EraIndex, SessionIndex};
 
-use sp_std::prelude::*;
+use sp_std::{collections::btree_map::BTreeMap, prelude::*};
 
 /// Mode of era-forcing.
 #[derive(
@@ -42,8 +42,62 @@ impl Default for Forcing {
 	}
 }
 
+/// What the elected sequencers are used for by the session manager.
+#[derive(
+	Copy,
+	Clone,
+	PartialEq,
+	Eq,
+	Encode,
+	Decode,
+	RuntimeDebug,
+	TypeInfo,
+	MaxEncodedLen,
+	serde::Serialize,
+	serde::Deserialize,
+)]
+pub enum SessionMode {
+	/// Only tag the elected sequencers, the session keeps the validators of the wrapped session
+	/// manager.
+	Tag,
+	/// The elected sequencers of an era become the validators of the session, falling back to
+	/// the invulnerables if none of them can validate.
+	Authority,
+}
+
+impl Default for SessionMode {
+	fn default() -> Self {
+		SessionMode::Tag
+	}
+}
+
+/// Era parameters which can be changed without a runtime upgrade.
+#[derive(
+	Copy,
+	Clone,
+	PartialEq,
+	Eq,
+	Encode,
+	Decode,
+	RuntimeDebug,
+	TypeInfo,
+	MaxEncodedLen,
+	serde::Serialize,
+	serde::Deserialize,
+)]
+pub struct EraConfig {
+	/// Number of sessions per era.
+	pub sessions_per_era: SessionIndex,
+	/// Number of eras to keep in history.
+	pub history_depth: u32,
+	/// Number of sequencers an election has to elect at least to succeed.
+	pub min_sequencer_count: u32,
+}
+
 /// Information regarding the active era (era in used in session).
-#[derive(Encode, Decode, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+#[derive(
+	Encode, Decode, RuntimeDebug, TypeInfo, MaxEncodedLen, serde::Serialize, serde::Deserialize,
+)]
 pub struct ActiveEraInfo {
 	/// Index of era.
 	pub index: EraIndex,
@@ -53,3 +107,163 @@ pub struct ActiveEraInfo {
 	/// Start is set on the first on_finalize of the era to guarantee usage of `Time`.
 	pub start: Option<u64>,
 }
+
+/// A sequencer elected for an era.
+#[derive(
+	Clone,
+	PartialEq,
+	Eq,
+	Encode,
+	Decode,
+	RuntimeDebug,
+	TypeInfo,
+	MaxEncodedLen,
+	serde::Serialize,
+	serde::Deserialize,
+)]
+pub struct ElectedSequencer<AccountId, Balance> {
+	/// The elected account.
+	pub who: AccountId,
+	/// Stake backing the account at the time of the election.
+	pub stake: Balance,
+	/// Position in the election, `0` being the highest stake.
+	///
+	/// Ties on stake are broken by the ascending order of the account id.
+	pub rank: u32,
+}
+
+/// Funds being unbonded, they can be withdrawn once `era` is reached.
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct UnlockChunk<Balance> {
+	/// Amount of funds to be unlocked.
+	pub value: Balance,
+	/// Era number at which point they can be withdrawn.
+	pub era: EraIndex,
+}
+
+/// Reward points of an era, used to split the era reward between the elected sequencers.
+#[derive(PartialEq, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct EraRewardPoints<AccountId: Ord> {
+	/// Total number of points, equals the sum of the individual points.
+	pub total: u32,
+	/// The points earned by each sequencer.
+	pub individual: BTreeMap<AccountId, u32>,
+}
+
+impl<AccountId: Ord> Default for EraRewardPoints<AccountId> {
+	fn default() -> Self {
+		EraRewardPoints { total: Default::default(), individual: BTreeMap::new() }
+	}
+}
+
+/// Something sequencers can be rewarded points through, for the active era.
+///
+/// Implemented by the sequencer pallet, for other pallets to reward the sequencers for their
+/// work, e.g. producing blocks.
+pub trait RewardSequencers<AccountId> {
+	/// Add reward points to the sequencers, for the active era.
+	fn reward_by_ids(sequencers_points: impl IntoIterator<Item = (AccountId, u32)>);
+}
+
+/// The stake of a delegator backing a sequencer operator.
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct IndividualExposure<AccountId, Balance> {
+	/// The delegator.
+	pub who: AccountId,
+	/// Amount delegated to the operator.
+	pub value: Balance,
+}
+
+/// A snapshot of the stake backing a sequencer operator.
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct Exposure<AccountId, Balance> {
+	/// The total stake, the own stake plus the delegations.
+	pub total: Balance,
+	/// The operator's own stake.
+	pub own: Balance,
+	/// The delegators backing the operator.
+	pub others: Vec<IndividualExposure<AccountId, Balance>>,
+}
+
+impl<AccountId, Balance: Default> Default for Exposure<AccountId, Balance> {
+	fn default() -> Self {
+		Exposure { total: Default::default(), own: Default::default(), others: Vec::new() }
+	}
+}
+
+/// A sequencer group of an era.
+#[derive(
+	Clone,
+	PartialEq,
+	Eq,
+	Encode,
+	Decode,
+	RuntimeDebug,
+	TypeInfo,
+	serde::Serialize,
+	serde::Deserialize,
+)]
+pub struct GroupInfo<AccountId, Balance> {
+	/// The members of the group, in the order they were assigned.
+	pub members: Vec<AccountId>,
+	/// The sum of the stake of the members.
+	pub total_stake: Balance,
+}
+
+/// When an era started and how long it lasted, in milliseconds.
+#[derive(
+	Copy,
+	Clone,
+	PartialEq,
+	Eq,
+	Encode,
+	Decode,
+	RuntimeDebug,
+	TypeInfo,
+	serde::Serialize,
+	serde::Deserialize,
+)]
+pub struct EraTiming {
+	/// The start of the era, in milliseconds since the unix epoch.
+	pub start: u64,
+	/// How long the era lasted, `None` while it is still active.
+	pub duration: Option<u64>,
+}
+
+sp_api::decl_runtime_apis! {
+	/// The API to query the eras, the elected sequencers and their groups.
+	pub trait SequencerApi<AccountId, Balance>
+	where
+		AccountId: Codec,
+		Balance: Codec,
+	{
+		/// The latest planned era, it might not be active yet.
+		fn current_era() -> Option<EraIndex>;
+
+		/// The active era.
+		fn active_era() -> Option<ActiveEraInfo>;
+
+		/// The sequencers elected for `era`, ordered by their rank.
+		fn era_sequencers(era: EraIndex) -> Vec<ElectedSequencer<AccountId, Balance>>;
+
+		/// The sequencer groups of `era`.
+		fn era_groups(era: EraIndex) -> Vec<GroupInfo<AccountId, Balance>>;
+
+		/// The stake of `who` in the next election, its own bond plus the delegations to it.
+		fn stake_of(who: AccountId) -> Balance;
+
+		/// The total bonded by the stakers, and their number.
+		fn restake_totals() -> (Balance, u32);
+
+		/// The index of the group `who` is a member of in `era`.
+		fn group_of(who: AccountId, era: EraIndex) -> Option<u32>;
+
+		/// The number of sessions until the next era becomes active, if eras are not forced off.
+		///
+		/// This is an estimate, the era comes later if its election fails.
+		fn next_era_eta_sessions() -> Option<SessionIndex>;
+
+		/// When `era` started and how long it lasted, if it is still in history.
+		fn era_timing(era: EraIndex) -> Option<EraTiming>;
+	}
+}
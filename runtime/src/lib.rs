@@ -113,6 +113,13 @@ pub type SignedExtra = (
 pub type UncheckedExtrinsic =
 	generic::UncheckedExtrinsic<Address, RuntimeCall, Signature, SignedExtra>;
 
+/// Migrations to apply on runtime upgrade.
+pub type Migrations = (
+	pallet_sequencer::migrations::v1::MigrateToV1<Runtime>,
+	pallet_sequencer::migrations::v2::MigrateToV2<Runtime>,
+	pallet_sequencer::migrations::v3::MigrateToV3<Runtime>,
+);
+
 /// Executive: handles dispatch to the various modules.
 pub type Executive = frame_executive::Executive<
 	Runtime,
@@ -120,6 +127,7 @@ pub type Executive = frame_executive::Executive<
 	frame_system::ChainContext<Runtime>,
 	Runtime,
 	AllPalletsWithSystem,
+	Migrations,
 >;
 
 /// Handles converting a weight scalar to a fee value, based on the scale and granularity of the
@@ -182,10 +190,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	spec_name: create_runtime_str!("template-parachain"),
 	impl_name: create_runtime_str!("template-parachain"),
 	authoring_version: 1,
-	spec_version: 1,
+	spec_version: 2,
 	impl_version: 0,
 	apis: RUNTIME_API_VERSIONS,
-	transaction_version: 1,
+	transaction_version: 2,
 	state_version: 1,
 };
 
@@ -333,7 +341,7 @@ impl pallet_timestamp::Config for Runtime {
 
 impl pallet_authorship::Config for Runtime {
 	type FindAuthor = pallet_session::FindAccountFromAuthorIndex<Self, Aura>;
-	type EventHandler = (CollatorSelection,);
+	type EventHandler = (CollatorSelection, SequencerPointsByAuthor);
 }
 
 parameter_types! {
@@ -355,7 +363,8 @@ impl pallet_balances::Config for Runtime {
 	type RuntimeHoldReason = RuntimeHoldReason;
 	type RuntimeFreezeReason = RuntimeFreezeReason;
 	type FreezeIdentifier = ();
-	type MaxHolds = ConstU32<0>;
+	// The sequencer pallet holds the restaked funds and the registration deposits.
+	type MaxHolds = ConstU32<2>;
 	type MaxFreezes = ConstU32<0>;
 }
 
@@ -559,6 +568,10 @@ construct_runtime!(
 		// Collator support. The order of these 4 are important and shall not change.
 		Authorship: pallet_authorship = 20,
 		CollatorSelection: pallet_collator_selection = 21,
+		// The sequencer genesis must be built before the session one, which elects the first
+		// sequencers out of the genesis stakers.
+		Sequencer: pallet_sequencer = 51,
+		SequencerStakers: pallet_bags_list = 52,
 		Session: pallet_session = 22,
 		Aura: pallet_aura = 23,
 		AuraExt: cumulus_pallet_aura_ext = 24,
@@ -571,7 +584,6 @@ construct_runtime!(
 
 		// Template
 		TemplatePallet: pallet_parachain_template = 50,
-		Sequencer: pallet_sequencer = 51,
 
 
         // Randomness
@@ -590,6 +602,7 @@ mod benches {
 		[pallet_sudo, Sudo]
 		[pallet_collator_selection, CollatorSelection]
 		[cumulus_pallet_xcmp_queue, XcmpQueue]
+		[pallet_sequencer, Sequencer]
 	);
 }
 
@@ -681,6 +694,46 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl ep_sequencer::SequencerApi<Block, AccountId, Balance> for Runtime {
+		fn current_era() -> Option<sp_staking::EraIndex> {
+			Sequencer::current_era()
+		}
+
+		fn active_era() -> Option<ep_sequencer::ActiveEraInfo> {
+			Sequencer::active_era()
+		}
+
+		fn era_sequencers(
+			era: sp_staking::EraIndex,
+		) -> Vec<ep_sequencer::ElectedSequencer<AccountId, Balance>> {
+			Sequencer::eras_sequencers(era).into_inner()
+		}
+
+		fn era_groups(era: sp_staking::EraIndex) -> Vec<ep_sequencer::GroupInfo<AccountId, Balance>> {
+			Sequencer::api_era_groups(era)
+		}
+
+		fn stake_of(who: AccountId) -> Balance {
+			Sequencer::election_weight(&who)
+		}
+
+		fn restake_totals() -> (Balance, u32) {
+			Sequencer::api_restake_totals()
+		}
+
+		fn group_of(who: AccountId, era: sp_staking::EraIndex) -> Option<u32> {
+			Sequencer::api_group_of(&who, era)
+		}
+
+		fn next_era_eta_sessions() -> Option<sp_staking::SessionIndex> {
+			Sequencer::api_next_era_eta_sessions(Session::current_session_index())
+		}
+
+		fn era_timing(era: sp_staking::EraIndex) -> Option<ep_sequencer::EraTiming> {
+			Sequencer::api_era_timing(era)
+		}
+	}
+
 	impl frame_system_rpc_runtime_api::AccountNonceApi<Block, AccountId, Nonce> for Runtime {
 		fn account_nonce(account: AccountId) -> Nonce {
 			System::account_nonce(account)
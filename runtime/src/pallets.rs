@@ -1 +1,2 @@
 mod sequencer;
+pub use sequencer::SequencerPointsByAuthor;
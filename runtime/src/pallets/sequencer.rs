@@ -1,17 +1,124 @@
 use crate::*;
+use frame_support::traits::{Get, Randomness};
+use pallet_sequencer::{BalancingConfig, RewardSequencers, SessionMode};
+use sp_runtime::traits::Hash as _;
 use sp_staking::SessionIndex;
 
 parameter_types! {
 	// Six sessions in an era (24 hours).
 	pub const SessionsPerEra: SessionIndex = 6;
+	// 5% of the elected stake is minted as reward every year.
+	pub const SequencerYearlyInflation: Perbill = Perbill::from_percent(5);
+	// Half of the era reward goes by the reward points, the other half by the stake.
+	pub const PointsRewardPortion: Perbill = Perbill::from_percent(50);
+	pub const RegistrationDepositBase: Balance = UNIT;
+	pub const RegistrationDepositPerByte: Balance = 10 * MICROUNIT;
+	// Balance the Phragmén solution to even out the stake backing the sequencers.
+	pub SequencerBalancing: Option<BalancingConfig> =
+		Some(BalancingConfig { iterations: 10, tolerance: 0 });
+	// The elected sequencers author the blocks.
+	pub const SequencerSessionMode: SessionMode = SessionMode::Authority;
+	pub const SequencerBagThresholds: &'static [Balance] = &SEQUENCER_BAG_THRESHOLDS;
+}
+
+/// Upper bounds of the bags of the staker list, doubling from one unit on.
+const SEQUENCER_BAG_THRESHOLDS: [Balance; 64] = {
+	let mut thresholds = [0; 64];
+	let mut i = 0;
+	while i < thresholds.len() {
+		thresholds[i] = UNIT << i;
+		i += 1;
+	}
+	thresholds
+};
+
+/// The invulnerable collators, authoring the blocks when no elected sequencer can.
+pub struct CollatorInvulnerables;
+
+impl Get<Vec<AccountId>> for CollatorInvulnerables {
+	fn get() -> Vec<AccountId> {
+		pallet_collator_selection::Invulnerables::<Runtime>::get().into_inner()
+	}
+}
+
+/// The latest local VRF output of the randomness pallet, mixed with the subject.
+///
+/// The seeds are drawn from the session hook, in `on_initialize` and at genesis, where the relay
+/// chain state proof is not available yet. So the output stored by the previous blocks is read
+/// instead of the relay chain epoch randomness. Until there is one, the parent hash is used.
+pub struct LocalVrfRandomness;
+
+impl Randomness<Hash, BlockNumber> for LocalVrfRandomness {
+	fn random(subject: &[u8]) -> (Hash, BlockNumber) {
+		let randomness = pallet_randomness::LocalVrfOutput::<Runtime>::get().unwrap_or_else(|| {
+			log::warn!(
+				target: "runtime::sequencer",
+				"No local VRF output yet, falling back to the parent hash for {:?}",
+				subject,
+			);
+			System::parent_hash()
+		});
+		(BlakeTwo256::hash_of(&(subject, randomness)), System::block_number())
+	}
+}
+
+/// Reward the block authors with sequencer reward points.
+pub struct SequencerPointsByAuthor;
+
+impl pallet_authorship::EventHandler<AccountId, BlockNumber> for SequencerPointsByAuthor {
+	fn note_author(author: AccountId) {
+		Sequencer::reward_by_ids([(author, 20)]);
+	}
+}
+
+impl pallet_bags_list::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type ScoreProvider = Sequencer;
+	type WeightInfo = ();
+	type BagThresholds = SequencerBagThresholds;
+	type Score = Balance;
 }
 
 impl pallet_sequencer::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
+	type Currency = Balances;
+	type RuntimeHoldReason = RuntimeHoldReason;
+	// The initial era config, which `set_config` can change later on.
 	type SessionsPerEra = SessionsPerEra;
 	type HistoryDepth = frame_support::traits::ConstU32<84>;
 	type MinSequencerCount = frame_support::traits::ConstU32<3>;
+	type MaxSequencers = frame_support::traits::ConstU32<100>;
+	type MaxCandidates = frame_support::traits::ConstU32<1000>;
+	type StakerList = SequencerStakers;
+	type BondingDuration = frame_support::traits::ConstU32<28>;
+	type MaxUnlockingChunks = frame_support::traits::ConstU32<32>;
+	type Election = pallet_sequencer::SequentialPhragmen<
+		Balances,
+		sp_staking::currency_to_vote::U128CurrencyToVote,
+		SequencerBalancing,
+	>;
+	type GroupAssignment = pallet_sequencer::GreedyBalance;
+	type Randomness = LocalVrfRandomness;
+	type AdminOrigin = EnsureRoot<AccountId>;
+	// Slashes can be cancelled during 27 eras, one less than the bonding duration.
+	type SlashDeferDuration = frame_support::traits::ConstU32<27>;
+	type MaxFieldLength = frame_support::traits::ConstU32<128>;
+	type MaxEndpoints = frame_support::traits::ConstU32<8>;
+	type RegistrationDepositBase = RegistrationDepositBase;
+	type RegistrationDepositPerByte = RegistrationDepositPerByte;
+	type MaxDelegators = frame_support::traits::ConstU32<64>;
+	type MaxUnappliedSlashes = frame_support::traits::ConstU32<512>;
+	// Slashed funds are burnt.
+	type Slash = ();
+	type RewardSource = pallet_sequencer::Minted<Balances, SequencerYearlyInflation>;
+	type PointsRewardPortion = PointsRewardPortion;
 	type UnixTime = Timestamp;
+	type SessionMode = SequencerSessionMode;
+	type ValidatorRegistration = Session;
+	type Invulnerables = CollatorInvulnerables;
+	// Estimated, not measured: to be replaced by the output of `benchmark pallet` under
+	// `weights` once the benchmarks have been run on the reference hardware.
+	type WeightInfo = pallet_sequencer::weights::SubstrateWeight<Runtime>;
 	// type ValidatorId = <Self as frame_system::Config>::AccountId;
 	// type ValidatorIdOf = pallet_collator_selection::IdentityCollator;
 }